@@ -6,6 +6,7 @@ fn elapsed_time(start: SystemTime, end: SystemTime) -> u128 {
     elapsed.as_micros()
 }
 
+#[allow(clippy::explicit_counter_loop)]
 fn test_strings(iters: i32) {
     let mut ido = Ido::new();
     let mut vals: Vec<String> = Vec::<String>::new();
@@ -27,6 +28,7 @@ fn test_strings(iters: i32) {
     println!("{:<20}: {}, {:.3}", "test_strings", elapsed, op);
 }
 
+#[allow(clippy::explicit_counter_loop)]
 fn test_integers(iters: i32) {
     let mut ido = Ido::new();
     let mut vals: Vec<i64> = Vec::<i64>::new();
@@ -5,6 +5,12 @@ use std::fmt;
 pub enum ExtransError {
     SetupError(String),
     EncodeError(String),
+    DecodeError(String),
+    FrameError(String),
+    ChecksumMismatch(String),
+    BodyLengthMismatch(String),
+    SessionError(String),
+    StoreError(String),
     PropertyNotFound(String),
     ParseError(String)
 }
@@ -14,6 +20,12 @@ impl fmt::Display for ExtransError {
         match self {
             ExtransError::SetupError(msg) => write!(f, "setup Error: {}", msg),
             ExtransError::EncodeError(msg) => write!(f, "encode Error: {}", msg),
+            ExtransError::DecodeError(msg) => write!(f, "decode Error: {}", msg),
+            ExtransError::FrameError(msg) => write!(f, "frame Error: {}", msg),
+            ExtransError::ChecksumMismatch(msg) => write!(f, "checksum mismatch: {}", msg),
+            ExtransError::BodyLengthMismatch(msg) => write!(f, "body length mismatch: {}", msg),
+            ExtransError::SessionError(msg) => write!(f, "session Error: {}", msg),
+            ExtransError::StoreError(msg) => write!(f, "store Error: {}", msg),
             ExtransError::PropertyNotFound(msg) => write!(f, "property not found: {}", msg),
             ExtransError::ParseError(msg) => write!(f, "failed to parse property: {}", msg),
         }
@@ -0,0 +1,393 @@
+use std::collections::HashMap;
+
+use crate::ExtransError;
+
+use super::component::{Component, ComponentFieldType};
+use super::dictionary_issue::DictionaryIssue;
+use super::field::Field;
+use super::group::Group;
+use super::message::Message;
+
+/// Identifies a dictionary cache file. The version is bumped whenever the layout changes so
+/// that caches written by older builds are rebuilt rather than misread.
+const MAGIC: &[u8; 8] = b"EXTDICT\x00";
+const VERSION: u32 = 1;
+
+/// 64 bit FNV-1a hash of the dictionary source, used to detect that the XML has changed.
+pub(crate) fn fingerprint(data: &[u8]) -> u64 {
+    data.iter().fold(0xcbf29ce484222325, |hash, b| (hash ^ *b as u64).wrapping_mul(0x100000001b3))
+}
+
+/// Appends little endian primitives to a cache buffer.
+pub(crate) struct CacheWriter {
+    m_buffer: Vec<u8>,
+}
+
+impl CacheWriter {
+    /// Starts a cache for a dictionary source of the given size and fingerprint.
+    pub(crate) fn new(source_len: u64, source_hash: u64) -> Self {
+        let mut writer = CacheWriter { m_buffer: Vec::with_capacity(64 * 1024) };
+        writer.m_buffer.extend_from_slice(MAGIC);
+        writer.write_u32(VERSION);
+        writer.write_u64(source_len);
+        writer.write_u64(source_hash);
+        writer
+    }
+
+    pub(crate) fn into_bytes(self) -> Vec<u8> {
+        self.m_buffer
+    }
+
+    pub(crate) fn write_u8(&mut self, value: u8) {
+        self.m_buffer.push(value);
+    }
+
+    pub(crate) fn write_bool(&mut self, value: bool) {
+        self.write_u8(value as u8);
+    }
+
+    pub(crate) fn write_u32(&mut self, value: u32) {
+        self.m_buffer.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub(crate) fn write_i32(&mut self, value: i32) {
+        self.m_buffer.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub(crate) fn write_u64(&mut self, value: u64) {
+        self.m_buffer.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub(crate) fn write_len(&mut self, len: usize) {
+        self.write_u32(len as u32);
+    }
+
+    pub(crate) fn write_str(&mut self, value: &str) {
+        self.write_len(value.len());
+        self.m_buffer.extend_from_slice(value.as_bytes());
+    }
+
+    pub(crate) fn write_opt_i32(&mut self, value: Option<i32>) {
+        self.write_bool(value.is_some());
+        if let Some(value) = value {
+            self.write_i32(value);
+        }
+    }
+
+    pub(crate) fn write_opt_str(&mut self, value: Option<&str>) {
+        self.write_bool(value.is_some());
+        if let Some(value) = value {
+            self.write_str(value);
+        }
+    }
+
+    /// Writes a field definition, its raw dictionary type included.
+    pub(crate) fn write_field(&mut self, field: &Field, field_type: &str) {
+        self.write_i32(field.get_tag());
+        self.write_str(field.get_name());
+        self.write_str(field_type);
+        self.write_len(field.iter_values().len());
+        for (value, description) in field.iter_values() {
+            self.write_str(value);
+            self.write_str(description);
+        }
+        self.write_opt_i32(field.get_length_tag());
+        self.write_opt_i32(field.get_data_tag());
+    }
+
+    fn write_entries(&mut self, component: &Component, entries: &[(ComponentFieldType, String, bool)]) {
+        self.write_len(entries.len());
+        for (cft, name, required) in entries {
+            self.write_u8(match cft {
+                ComponentFieldType::FIELD => 0,
+                ComponentFieldType::COMPONENT => 1,
+                ComponentFieldType::GROUP => 2,
+            });
+            self.write_str(name);
+            self.write_bool(*required);
+            if let ComponentFieldType::GROUP = cft {
+                let group_fields = component.get_group_fields(name.clone()).unwrap_or_default();
+                self.write_entries(component, &group_fields);
+            }
+        }
+    }
+
+    /// Writes a component, message, header or trailer definition as declared in the XML.
+    pub(crate) fn write_component(&mut self, name: &str, component: &Component) {
+        self.write_str(name);
+        self.write_entries(component, component.iter().as_slice());
+    }
+
+    fn write_layout<'a>(&mut self, fields: impl ExactSizeIterator<Item = &'a Field>, is_required: impl Fn(i32) -> bool, get_group: impl Fn(i32) -> Option<&'a Group>) {
+        self.write_len(fields.len());
+        for field in fields {
+            self.write_i32(field.get_tag());
+            self.write_bool(is_required(field.get_tag()));
+            match get_group(field.get_tag()) {
+                Some(group) => {
+                    self.write_bool(true);
+                    self.write_group(group);
+                },
+                None => self.write_bool(false),
+            }
+        }
+    }
+
+    fn write_group(&mut self, group: &Group) {
+        self.write_layout(group.iter_fields(), |tag| group.is_required(tag), |tag| group.get_group(tag));
+    }
+
+    /// Writes a resolved message, components expanded and groups nested, fields by tag.
+    pub(crate) fn write_message(&mut self, message: &Message) {
+        self.write_str(message.get_name());
+        self.write_str(message.get_type());
+        self.write_str(message.get_category());
+        self.write_layout(message.iter_fields(), |tag| message.is_required(tag), |tag| message.get_group(tag));
+    }
+
+    pub(crate) fn write_issue(&mut self, issue: &DictionaryIssue) {
+        match issue {
+            DictionaryIssue::UnknownField { context, name } => {
+                self.write_u8(0);
+                self.write_str(context);
+                self.write_str(name);
+            },
+            DictionaryIssue::UndefinedComponent { context, name } => {
+                self.write_u8(1);
+                self.write_str(context);
+                self.write_str(name);
+            },
+            DictionaryIssue::DuplicateTag { tag, name, existing } => {
+                self.write_u8(2);
+                self.write_i32(*tag);
+                self.write_str(name);
+                self.write_str(existing);
+            },
+            DictionaryIssue::DuplicateName { name, tag, existing } => {
+                self.write_u8(3);
+                self.write_str(name);
+                self.write_i32(*tag);
+                self.write_i32(*existing);
+            },
+            DictionaryIssue::InvalidGroupCountType { context, name, field_type } => {
+                self.write_u8(4);
+                self.write_str(context);
+                self.write_str(name);
+                self.write_str(field_type);
+            },
+            DictionaryIssue::UnpairedDataField { name } => {
+                self.write_u8(5);
+                self.write_str(name);
+            },
+            DictionaryIssue::MissingAttribute { context, element, attribute } => {
+                self.write_u8(6);
+                self.write_str(context);
+                self.write_str(element);
+                self.write_str(attribute);
+            },
+            DictionaryIssue::InvalidAttribute { context, attribute, value } => {
+                self.write_u8(7);
+                self.write_str(context);
+                self.write_str(attribute);
+                self.write_str(value);
+            },
+            DictionaryIssue::ConflictingFieldType { name, tag, field_type, existing } => {
+                self.write_u8(8);
+                self.write_str(name);
+                self.write_i32(*tag);
+                self.write_str(field_type);
+                self.write_str(existing);
+            },
+            DictionaryIssue::ConflictingMessageName { msgtype, name, existing } => {
+                self.write_u8(9);
+                self.write_str(msgtype);
+                self.write_str(name);
+                self.write_str(existing);
+            },
+        }
+    }
+}
+
+/// Reads the primitives written by `CacheWriter`. Every read fails with a SetupError on a
+/// truncated or corrupt cache.
+pub(crate) struct CacheReader<'a> {
+    m_data: &'a [u8],
+    m_pos: usize,
+}
+
+fn corrupt(what: &str) -> ExtransError {
+    ExtransError::SetupError(format!("corrupt FIX dictionary cache: {}", what))
+}
+
+impl<'a> CacheReader<'a> {
+    /// Checks the cache header, returning None if the cache was written by another format
+    /// version or for a different dictionary source.
+    pub(crate) fn new(data: &'a [u8], source_len: u64, source_hash: u64) -> Result<Option<Self>, ExtransError> {
+        let mut reader = CacheReader { m_data: data, m_pos: 0 };
+        if reader.read_bytes(MAGIC.len())? != MAGIC {
+            return Err(corrupt("not a dictionary cache"));
+        }
+        if reader.read_u32()? != VERSION || reader.read_u64()? != source_len || reader.read_u64()? != source_hash {
+            return Ok(None);
+        }
+        Ok(Some(reader))
+    }
+
+    pub(crate) fn is_at_end(&self) -> bool {
+        self.m_pos == self.m_data.len()
+    }
+
+    fn read_bytes(&mut self, len: usize) -> Result<&'a [u8], ExtransError> {
+        let end = self.m_pos.checked_add(len).filter(|end| *end <= self.m_data.len()).ok_or_else(|| corrupt("unexpected end of file"))?;
+        let bytes = &self.m_data[self.m_pos..end];
+        self.m_pos = end;
+        Ok(bytes)
+    }
+
+    pub(crate) fn read_u8(&mut self) -> Result<u8, ExtransError> {
+        Ok(self.read_bytes(1)?[0])
+    }
+
+    pub(crate) fn read_bool(&mut self) -> Result<bool, ExtransError> {
+        match self.read_u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(corrupt("invalid flag")),
+        }
+    }
+
+    pub(crate) fn read_u32(&mut self) -> Result<u32, ExtransError> {
+        Ok(u32::from_le_bytes(self.read_bytes(4)?.try_into().unwrap()))
+    }
+
+    pub(crate) fn read_i32(&mut self) -> Result<i32, ExtransError> {
+        Ok(i32::from_le_bytes(self.read_bytes(4)?.try_into().unwrap()))
+    }
+
+    pub(crate) fn read_u64(&mut self) -> Result<u64, ExtransError> {
+        Ok(u64::from_le_bytes(self.read_bytes(8)?.try_into().unwrap()))
+    }
+
+    pub(crate) fn read_len(&mut self) -> Result<usize, ExtransError> {
+        Ok(self.read_u32()? as usize)
+    }
+
+    pub(crate) fn read_string(&mut self) -> Result<String, ExtransError> {
+        let len = self.read_len()?;
+        let bytes = self.read_bytes(len)?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| corrupt("invalid string"))
+    }
+
+    pub(crate) fn read_opt_i32(&mut self) -> Result<Option<i32>, ExtransError> {
+        if self.read_bool()? { Ok(Some(self.read_i32()?)) } else { Ok(None) }
+    }
+
+    pub(crate) fn read_opt_string(&mut self) -> Result<Option<String>, ExtransError> {
+        if self.read_bool()? { Ok(Some(self.read_string()?)) } else { Ok(None) }
+    }
+
+    /// Reads a field definition and its raw dictionary type.
+    pub(crate) fn read_field(&mut self) -> Result<(Field, String), ExtransError> {
+        let tag = self.read_i32()?;
+        let name = self.read_string()?;
+        let field_type = self.read_string()?;
+        let mut field = Field::new(tag, name, field_type.clone());
+        for _ in 0..self.read_len()? {
+            let value = self.read_string()?;
+            let description = self.read_string()?;
+            field.add_value(value, description);
+        }
+        if let Some(length_tag) = self.read_opt_i32()? {
+            field.set_length_tag(length_tag);
+        }
+        if let Some(data_tag) = self.read_opt_i32()? {
+            field.set_data_tag(data_tag);
+        }
+        Ok((field, field_type))
+    }
+
+    fn read_entries(&mut self, component: &mut Component) -> Result<Vec<(ComponentFieldType, String, bool)>, ExtransError> {
+        let mut entries = Vec::new();
+        for _ in 0..self.read_len()? {
+            let cft = match self.read_u8()? {
+                0 => ComponentFieldType::FIELD,
+                1 => ComponentFieldType::COMPONENT,
+                2 => ComponentFieldType::GROUP,
+                _ => return Err(corrupt("invalid component entry")),
+            };
+            let name = self.read_string()?;
+            let required = self.read_bool()?;
+            if let ComponentFieldType::GROUP = cft {
+                let group_fields = self.read_entries(component)?;
+                component.add_nested_group(name.clone(), group_fields);
+            }
+            entries.push((cft, name, required));
+        }
+        Ok(entries)
+    }
+
+    /// Reads a definition written by `CacheWriter::write_component`.
+    pub(crate) fn read_component(&mut self) -> Result<Component, ExtransError> {
+        let mut component = Component::new(self.read_string()?);
+        let entries = self.read_entries(&mut component)?;
+        component.add_entries(entries);
+        Ok(component)
+    }
+
+    /// Reads the fields of a message or group entry in declared order, each with its nested
+    /// group if it is a group count field.
+    fn read_layout(&mut self, fields: &HashMap<i32, Field>) -> Result<Vec<(Field, Option<Group>, bool)>, ExtransError> {
+        let mut layout = Vec::new();
+        for _ in 0..self.read_len()? {
+            let tag = self.read_i32()?;
+            let field = fields.get(&tag).cloned().ok_or_else(|| corrupt(&format!("undefined tag {}", tag)))?;
+            let required = self.read_bool()?;
+            let group = if self.read_bool()? { Some(self.read_group(fields, tag)?) } else { None };
+            layout.push((field, group, required));
+        }
+        Ok(layout)
+    }
+
+    fn read_group(&mut self, fields: &HashMap<i32, Field>, parent_tag: i32) -> Result<Group, ExtransError> {
+        let mut group = Group::new(parent_tag);
+        for (field, nested, required) in self.read_layout(fields)? {
+            match nested {
+                Some(nested) => group.add_group(field, nested, required),
+                None => group.add_field(field, required),
+            }
+        }
+        Ok(group)
+    }
+
+    /// Reads a message written by `CacheWriter::write_message`, fields taken from `fields`.
+    pub(crate) fn read_message(&mut self, fields: &HashMap<i32, Field>) -> Result<Message, ExtransError> {
+        let name = self.read_string()?;
+        let msgtype = self.read_string()?;
+        let msgcat = self.read_string()?;
+        let mut message = Message::new(name, msgtype, msgcat);
+        for (field, group, required) in self.read_layout(fields)? {
+            match group {
+                Some(group) => message.add_group(field, group, required),
+                None => message.add_field(field, required),
+            }
+        }
+        Ok(message)
+    }
+
+    pub(crate) fn read_issue(&mut self) -> Result<DictionaryIssue, ExtransError> {
+        let issue = match self.read_u8()? {
+            0 => DictionaryIssue::UnknownField { context: self.read_string()?, name: self.read_string()? },
+            1 => DictionaryIssue::UndefinedComponent { context: self.read_string()?, name: self.read_string()? },
+            2 => DictionaryIssue::DuplicateTag { tag: self.read_i32()?, name: self.read_string()?, existing: self.read_string()? },
+            3 => DictionaryIssue::DuplicateName { name: self.read_string()?, tag: self.read_i32()?, existing: self.read_i32()? },
+            4 => DictionaryIssue::InvalidGroupCountType { context: self.read_string()?, name: self.read_string()?, field_type: self.read_string()? },
+            5 => DictionaryIssue::UnpairedDataField { name: self.read_string()? },
+            6 => DictionaryIssue::MissingAttribute { context: self.read_string()?, element: self.read_string()?, attribute: self.read_string()? },
+            7 => DictionaryIssue::InvalidAttribute { context: self.read_string()?, attribute: self.read_string()?, value: self.read_string()? },
+            8 => DictionaryIssue::ConflictingFieldType { name: self.read_string()?, tag: self.read_i32()?, field_type: self.read_string()?, existing: self.read_string()? },
+            9 => DictionaryIssue::ConflictingMessageName { msgtype: self.read_string()?, name: self.read_string()?, existing: self.read_string()? },
+            _ => return Err(corrupt("invalid dictionary issue")),
+        };
+        Ok(issue)
+    }
+}
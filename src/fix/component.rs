@@ -2,6 +2,12 @@ use std::collections::HashMap;
 use std::slice::Iter;
 use std::fmt;
 
+use super::field::Field;
+use super::group::Group;
+use super::message::Message;
+
+#[derive(Clone)]
+#[allow(clippy::upper_case_acronyms)]
 pub enum ComponentFieldType {
     FIELD,
     COMPONENT,
@@ -10,65 +16,135 @@ pub enum ComponentFieldType {
 
 impl fmt::Display for ComponentFieldType {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let s = match self {
-            &ComponentFieldType::FIELD => "Field",
-            &ComponentFieldType::COMPONENT => "Component",
-            &ComponentFieldType::GROUP => "Group",
+        let s = match *self {
+            ComponentFieldType::FIELD => "Field",
+            ComponentFieldType::COMPONENT => "Component",
+            ComponentFieldType::GROUP => "Group",
         };
         write!(f, "{}", s)
     }
 }
 
+#[derive(Clone)]
 pub struct Component {
-    _m_name: String,
-    m_fields: Vec<(ComponentFieldType, String)>, 
-    m_groups: HashMap<String, Vec<String>>,
+    m_name: String,
+    m_fields: Vec<(ComponentFieldType, String, bool)>, 
+    m_groups: HashMap<String, Vec<(ComponentFieldType, String, bool)>>,
 }
 
 impl Component {
     pub fn new(name: String) -> Self {
         Component {
-            _m_name: (name),
+            m_name: (name),
             m_fields: Vec::new(),
             m_groups: HashMap::new(),
         }
     }
 
-    pub fn add_field(&mut self, name: String) {
-        self.m_fields.push((ComponentFieldType::FIELD, name))
+    pub fn get_name(&self) -> &str {
+        &self.m_name
+    }
+
+    pub fn add_field(&mut self, name: String, required: bool) {
+        self.m_fields.push((ComponentFieldType::FIELD, name, required))
     }
 
-    pub fn add_component(&mut self, name: String) {
-        self.m_fields.push((ComponentFieldType::COMPONENT, name))
+    pub fn add_component(&mut self, name: String, required: bool) {
+        self.m_fields.push((ComponentFieldType::COMPONENT, name, required))
     }
 
-    pub fn add_group(&mut self, name: String, group_fields: Vec<String>) {
-        self.m_fields.push((ComponentFieldType::GROUP, name.clone()));
+    /// Adds a repeating group, `group_fields` holding the type, name and required flag of each
+    /// group entry member in declared order.
+    pub fn add_group(&mut self, name: String, required: bool, group_fields: Vec<(ComponentFieldType, String, bool)>) {
+        self.m_fields.push((ComponentFieldType::GROUP, name.clone(), required));
         self.m_groups.insert(name, group_fields);
     }
 
-    pub fn set_fields(&mut self, fields: Vec<(ComponentFieldType, String)>) {
-        self.m_fields = fields;
+    /// Adds fields, components and groups in declared order. The layout of each group must
+    /// already have been recorded with `add_nested_group`.
+    pub fn add_entries(&mut self, entries: Vec<(ComponentFieldType, String, bool)>) {
+        for (cft, name, required) in entries {
+            match cft {
+                ComponentFieldType::FIELD => self.add_field(name, required),
+                ComponentFieldType::COMPONENT => self.add_component(name, required),
+                ComponentFieldType::GROUP => {
+                    let group_fields = self.get_group_fields(name.clone()).unwrap_or_default();
+                    self.add_group(name, required, group_fields);
+                },
+            }
+        }
+    }
+
+    /// Records the layout of a group nested within one of the component's groups. Group
+    /// names are unique count field names, so nested groups share the component group map.
+    pub fn add_nested_group(&mut self, name: String, group_fields: Vec<(ComponentFieldType, String, bool)>) {
+        self.m_groups.insert(name, group_fields);
     }
 
-    pub fn iter(&self) -> Iter<(ComponentFieldType, String)> {
+    pub fn iter(&self) -> Iter<'_, (ComponentFieldType, String, bool)> {
         self.m_fields.iter()
     }
 
-    pub fn get_group_fields(&self, group_name: String) -> Option<Vec<String>> {
-        if let Some(fields) = self.m_groups.get(&group_name) {
-            Some(fields.clone())
-        } else {
-            None
+    pub fn get_group_fields(&self, group_name: String) -> Option<Vec<(ComponentFieldType, String, bool)>> {
+        self.m_groups.get(&group_name).cloned()
+    }
+
+    /// Merges an overlay definition into the component. Entries already present keep their
+    /// position and take the overlay's type and required flag, new entries are appended. Group
+    /// layouts are merged the same way.
+    pub fn merge(&mut self, overlay: &Component) {
+        Self::merge_entries(&mut self.m_fields, &overlay.m_fields);
+        for (name, group_fields) in overlay.m_groups.iter() {
+            match self.m_groups.get_mut(name) {
+                Some(existing) => Self::merge_entries(existing, group_fields),
+                None => { self.m_groups.insert(name.clone(), group_fields.clone()); },
+            }
+        }
+    }
+
+    fn merge_entries(entries: &mut Vec<(ComponentFieldType, String, bool)>, overlay: &[(ComponentFieldType, String, bool)]) {
+        for (cft, name, required) in overlay {
+            match entries.iter_mut().find(|(_, n, _)| n == name) {
+                Some(entry) => {
+                    entry.0 = cft.clone();
+                    entry.2 = *required;
+                },
+                None => entries.push((cft.clone(), name.clone(), *required)),
+            }
         }
     }
 }
 
 impl fmt::Display for Component {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        for (t, n)in self.m_fields.iter() {
-            writeln!(f, "{} {}", t, n)?;
+        for (t, n, r) in self.m_fields.iter() {
+            writeln!(f, "{} {} {}", t, n, if *r { "required" } else { "optional" })?;
         } 
         Ok(())
     }
-}
\ No newline at end of file
+}
+/// Implemented by the layouts a component can be expanded into, messages and group entries.
+pub trait FieldContainer {
+    fn add_field(&mut self, field: Field, required: bool);
+    fn add_group(&mut self, field: Field, group: Group, required: bool);
+}
+
+impl FieldContainer for Message {
+    fn add_field(&mut self, field: Field, required: bool) {
+        Message::add_field(self, field, required)
+    }
+
+    fn add_group(&mut self, field: Field, group: Group, required: bool) {
+        Message::add_group(self, field, group, required)
+    }
+}
+
+impl FieldContainer for Group {
+    fn add_field(&mut self, field: Field, required: bool) {
+        Group::add_field(self, field, required)
+    }
+
+    fn add_group(&mut self, field: Field, group: Group, required: bool) {
+        Group::add_group(self, field, group, required)
+    }
+}
@@ -0,0 +1,274 @@
+use crate::ExtransError;
+use crate::ido::{Ido, IdoItem, IdoItemType, IdoKeyT};
+
+use super::dictionary::Dictionary;
+use super::encoder::{checksum, SOH, MSG_TYPE};
+use super::strictness::Strictness;
+use super::group::Group;
+
+/// Splits a raw SOH delimited message into (tag, value) pairs without copying the values.
+///
+/// A DATA or XMLDATA field following its LENGTH field is read by length rather than up to the
+/// next SOH, so binary payloads containing SOH bytes are kept intact.
+pub(crate) fn tokenize<'a>(dictionary: &Dictionary, data: &'a [u8]) -> Result<Vec<(IdoKeyT, &'a [u8])>, ExtransError> {
+    let mut fields: Vec<(IdoKeyT, &[u8])> = Vec::with_capacity(32);
+    let mut pos = 0;
+    let mut data_length: Option<(IdoKeyT, usize)> = None;
+
+    while pos < data.len() {
+        if let Some((data_tag, length)) = data_length.take() {
+            if let Some(end) = read_data_field(data, pos, data_tag, length)? {
+                fields.push((data_tag, &data[end - length..end]));
+                pos = end + 1;
+                continue;
+            }
+        }
+
+        let end = match data[pos..].iter().position(|b| *b == SOH) {
+            Some(offset) => pos + offset,
+            None => return Err(ExtransError::DecodeError(format!("field at offset {} is not SOH terminated", pos))),
+        };
+
+        let field = &data[pos..end];
+        let equals = match field.iter().position(|b| *b == b'=') {
+            Some(equals) => equals,
+            None => return Err(ExtransError::DecodeError(format!("field at offset {} has no '=' separator", pos))),
+        };
+
+        let tag = parse_tag(&field[..equals])?;
+        let value = &field[equals + 1..];
+        if let Some(data_tag) = dictionary.get_field(tag).and_then(|f| f.get_data_tag()) {
+            data_length = std::str::from_utf8(value).ok()
+                .and_then(|v| v.parse::<usize>().ok())
+                .map(|length| (data_tag, length));
+        }
+
+        fields.push((tag, value));
+        pos = end + 1;
+    }
+
+    Ok(fields)
+}
+
+/// Reads the data field expected at `pos`, returning the offset of its SOH terminator, or None if
+/// the field at `pos` is not the data field.
+fn read_data_field(data: &[u8], pos: usize, data_tag: IdoKeyT, length: usize) -> Result<Option<usize>, ExtransError> {
+    let prefix = format!("{}=", data_tag);
+    if !data[pos..].starts_with(prefix.as_bytes()) {
+        return Ok(None);
+    }
+
+    match (pos + prefix.len()).checked_add(length) {
+        Some(end) if end < data.len() && data[end] == SOH => Ok(Some(end)),
+        _ => Err(ExtransError::DecodeError(format!("data field {} is not {} bytes long", data_tag, length))),
+    }
+}
+
+/// Returns the value of a tag in a raw message without tokenizing it, e.g. to find the
+/// BeginString(8) or ApplVerID(1128) a message must be dispatched on.
+pub(crate) fn peek_field(data: &[u8], tag: IdoKeyT) -> Option<&str> {
+    let prefix = format!("{}=", tag);
+    let start = if data.starts_with(prefix.as_bytes()) {
+        prefix.len()
+    } else {
+        let field = format!("\x01{}", prefix);
+        data.windows(field.len()).position(|w| w == field.as_bytes())? + field.len()
+    };
+    let end = start + data[start..].iter().position(|b| *b == SOH)?;
+    std::str::from_utf8(&data[start..end]).ok()
+}
+
+/// Verifies BodyLength(9) and CheckSum(10) against the bytes of a raw message. The message must
+/// start with BeginString(8) and BodyLength(9) and end with CheckSum(10).
+pub(crate) fn verify(data: &[u8], strictness: Strictness) -> Result<(), ExtransError> {
+    if strictness == Strictness::IGNORE {
+        return Ok(());
+    }
+
+    let result = verify_body_length(data).and_then(|_| verify_checksum(data));
+    match (result, strictness) {
+        (Err(e), Strictness::WARN) => {
+            log::warn!("accepting FIX message: {}", e);
+            Ok(())
+        },
+        (result, _) => result,
+    }
+}
+
+/// Returns the value of the field starting at `pos` and the offset following its SOH.
+fn field_at<'a>(data: &'a [u8], pos: usize, prefix: &[u8]) -> Option<(&'a [u8], usize)> {
+    if !data.get(pos..)?.starts_with(prefix) {
+        return None;
+    }
+    let start = pos + prefix.len();
+    let end = start + data[start..].iter().position(|b| *b == SOH)?;
+    Some((&data[start..end], end + 1))
+}
+
+/// Returns the offset of the CheckSum(10) field, which must be the last field of the message.
+fn trailer_start(data: &[u8]) -> Option<usize> {
+    if data.last() != Some(&SOH) {
+        return None;
+    }
+    let start = data[..data.len() - 1].iter().rposition(|b| *b == SOH).map(|pos| pos + 1).unwrap_or(0);
+    if data[start..].starts_with(b"10=") { Some(start) } else { None }
+}
+
+fn verify_body_length(data: &[u8]) -> Result<(), ExtransError> {
+    let body_start = field_at(data, 0, b"8=")
+        .and_then(|(_, next)| field_at(data, next, b"9="))
+        .and_then(|(value, next)| Some((std::str::from_utf8(value).ok()?.parse::<usize>().ok()?, next)));
+    let (body_length, body_start) = match body_start {
+        Some(body_start) => body_start,
+        None => return Err(ExtransError::BodyLengthMismatch("message does not start with BeginString(8) and a valid BodyLength(9)".to_string())),
+    };
+
+    let received = match trailer_start(data) {
+        Some(trailer_start) if trailer_start >= body_start => trailer_start - body_start,
+        _ => return Err(ExtransError::ChecksumMismatch("message does not end with CheckSum(10)".to_string())),
+    };
+    if received != body_length {
+        return Err(ExtransError::BodyLengthMismatch(format!("BodyLength(9) is {} but {} bytes were received", body_length, received)));
+    }
+    Ok(())
+}
+
+fn verify_checksum(data: &[u8]) -> Result<(), ExtransError> {
+    let trailer_start = match trailer_start(data) {
+        Some(trailer_start) => trailer_start,
+        None => return Err(ExtransError::ChecksumMismatch("message does not end with CheckSum(10)".to_string())),
+    };
+
+    let value = &data[trailer_start + 3..data.len() - 1];
+    let expected = checksum(&data[..trailer_start]);
+    match std::str::from_utf8(value).ok().and_then(|v| v.parse::<u32>().ok()) {
+        Some(received) if received == expected => Ok(()),
+        _ => Err(ExtransError::ChecksumMismatch(format!("CheckSum(10) is '{}' but {:03} was calculated", String::from_utf8_lossy(value), expected))),
+    }
+}
+
+fn parse_tag(tag: &[u8]) -> Result<IdoKeyT, ExtransError> {
+    match std::str::from_utf8(tag).ok().and_then(|s| s.parse::<IdoKeyT>().ok()) {
+        Some(tag) if tag > 0 => Ok(tag),
+        _ => Err(ExtransError::DecodeError(format!("invalid tag '{}'", String::from_utf8_lossy(tag)))),
+    }
+}
+
+fn to_str(tag: IdoKeyT, value: &[u8]) -> Result<&str, ExtransError> {
+    std::str::from_utf8(value).map_err(|_| ExtransError::DecodeError(format!("tag {} value is not valid UTF-8", tag)))
+}
+
+fn set_value(dictionary: &Dictionary, ido: &mut Ido, tag: IdoKeyT, value: &[u8]) -> Result<(), ExtransError> {
+    let field_type = dictionary.get_field(tag).map(|f| f.get_field_type());
+    if field_type.map(|t| t.is_data()).unwrap_or(false) {
+        ido.set_bytes(&tag, value.to_vec());
+        return Ok(());
+    }
+
+    let value = to_str(tag, value)?;
+    let field_type = match field_type {
+        Some(field_type) => field_type,
+        None => {
+            ido.set_string(&tag, value.to_string());
+            return Ok(());
+        }
+    };
+
+    let invalid = || ExtransError::DecodeError(format!("tag {} has invalid {} value '{}'", tag, field_type, value));
+    if !field_type.is_valid(value) {
+        return Err(invalid());
+    }
+
+    if field_type.is_integer() {
+        ido.set_integer(&tag, value.parse::<i64>().map_err(|_| invalid())?);
+    } else if field_type.is_float() {
+        ido.set_f64(&tag, value.parse::<f64>().map_err(|_| invalid())?);
+    } else if field_type.is_datetime() {
+        ido.set_datetime(&tag, field_type.parse_datetime(value).ok_or_else(invalid)?);
+    } else {
+        ido.set_string(&tag, value.to_string());
+    }
+    Ok(())
+}
+
+/// Decodes the repeating group starting at `idx`, which must hold the group count field, into an
+/// Ido array keyed by the count tag, empty for a zero count. The count declared is kept as the
+/// integer value of the array item so that the validator can report entries beyond it, fewer
+/// entries than declared are an error. Returns the index of the first field following the group.
+fn decode_group(dictionary: &Dictionary, group: &Group, fields: &[(IdoKeyT, &[u8])], mut idx: usize, ido: &mut Ido) -> Result<usize, ExtransError> {
+    let (count_tag, value) = fields[idx];
+    let count = to_str(count_tag, value)?.parse::<usize>()
+        .map_err(|_| ExtransError::DecodeError(format!("group {} has invalid count '{}'", count_tag, String::from_utf8_lossy(value))))?;
+    idx += 1;
+
+    let mut array = IdoItem::new();
+    array.m_type = IdoItemType::ARRAY;
+    array.m_integer = count as i64;
+
+    // entries beyond the count are read as long as they start with the delimiter field
+    while array.m_array.len() < count || (idx < fields.len() && group.is_first_tag(fields[idx].0)) {
+        if idx >= fields.len() || !group.is_first_tag(fields[idx].0) {
+            return Err(ExtransError::DecodeError(format!("group {} entry {} of {} does not start with the delimiter field", count_tag, array.m_array.len() + 1, count)));
+        }
+
+        let mut entry = Ido::new();
+        loop {
+            if let Some(nested) = group.get_group(fields[idx].0) {
+                idx = decode_group(dictionary, nested, fields, idx, &mut entry)?;
+            } else {
+                set_value(dictionary, &mut entry, fields[idx].0, fields[idx].1)?;
+                idx += 1;
+            }
+
+            if idx >= fields.len() || !group.has_tag(fields[idx].0) || group.is_first_tag(fields[idx].0) {
+                break;
+            }
+        }
+
+        array.m_array.push(entry);
+    }
+
+    ido.set_item(&count_tag, array);
+    Ok(idx)
+}
+
+/// Decodes a raw SOH delimited FIX tag=value message into an Ido keyed by tag number.
+///
+/// Values are typed according to the dictionary field definitions, and repeating groups
+/// defined for the message type are decoded into Ido arrays keyed by the group count tag.
+/// BodyLength(9) and CheckSum(10) are verified first according to `strictness`.
+pub(crate) fn decode(dictionary: &Dictionary, data: &[u8], strictness: Strictness) -> Result<Ido, ExtransError> {
+    verify(data, strictness)?;
+    let fields = tokenize(dictionary, data)?;
+    decode_fields(dictionary, &fields)
+}
+
+/// Decodes the (tag, value) pairs produced by `tokenize` into an Ido.
+pub(crate) fn decode_fields(dictionary: &Dictionary, fields: &[(IdoKeyT, &[u8])]) -> Result<Ido, ExtransError> {
+    let msgtype = match fields.iter().find(|(tag, _)| *tag == MSG_TYPE) {
+        Some((tag, value)) => to_str(*tag, value)?,
+        None => return Err(ExtransError::DecodeError("message has no MsgType(35)".to_string())),
+    };
+
+    let message = match dictionary.get_message(msgtype) {
+        Some(message) => message,
+        None => return Err(ExtransError::DecodeError(format!("message type '{}' is not defined in the dictionary", msgtype))),
+    };
+
+    let mut ido = Ido::new();
+    let mut idx = 0;
+    while idx < fields.len() {
+        let (tag, value) = fields[idx];
+        let group = dictionary.get_header().get_group(tag)
+            .or_else(|| message.get_group(tag))
+            .or_else(|| dictionary.get_trailer().get_group(tag));
+        if let Some(group) = group {
+            idx = decode_group(dictionary, group, fields, idx, &mut ido)?;
+        } else {
+            set_value(dictionary, &mut ido, tag, value)?;
+            idx += 1;
+        }
+    }
+
+    Ok(ido)
+}
@@ -1,9 +1,12 @@
 use crate::ExtransError;
 
-use super::field::Field;
-use super::component::{Component, ComponentFieldType};
+use super::field::{Field, FieldType};
+use super::component::{Component, ComponentFieldType, FieldContainer};
 use super::message::Message;
 use super::group::Group;
+use super::dictionary_issue::DictionaryIssue;
+use super::cache::{self, CacheReader, CacheWriter};
+use super::orchestra::{self, Repository};
 
 use std::str::FromStr;
 use std::fs::File;
@@ -11,25 +14,118 @@ use std::io::Read;
 use std::path::Path;
 use std::collections::HashMap;
 
+fn is_required(node: &roxmltree::Node) -> bool {
+    node.attribute("required") == Some("Y")
+}
+
+/// Maps a BeginString to its ApplVerID(1128) value, the application version FIXT.1.1 uses to
+/// identify the FIX version of application messages.
+fn begin_string_to_appl_ver_id(begin_string: &str) -> Option<&'static str> {
+    match begin_string {
+        "FIX.2.7" => Some("0"),
+        "FIX.3.0" => Some("1"),
+        "FIX.4.0" => Some("2"),
+        "FIX.4.1" => Some("3"),
+        "FIX.4.2" => Some("4"),
+        "FIX.4.3" => Some("5"),
+        "FIX.4.4" => Some("6"),
+        "FIX.5.0" => Some("7"),
+        "FIX.5.0SP1" => Some("8"),
+        "FIX.5.0SP2" => Some("9"),
+        _ => None,
+    }
+}
+
+#[derive(Clone)]
 pub struct Dictionary {
+    m_begin_string: String,
+    m_appl_ver_id: Option<String>,
     m_fix_fields: HashMap<i32, Field>,
     m_field_to_tag: HashMap<String, i32>,
     m_field_to_type: HashMap<String, String>,
     m_components: HashMap<String, Component>,
     m_messages: HashMap<String, Message>,
+    m_header: Message,
+    m_trailer: Message,
+    m_message_defs: HashMap<String, Component>,
+    m_header_def: Component,
+    m_trailer_def: Component,
+    m_issues: Vec<DictionaryIssue>,
+}
+
+impl Default for Dictionary {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Dictionary {
     pub fn new() -> Self {
         Dictionary {
+            m_begin_string: String::new(),
+            m_appl_ver_id: None,
             m_fix_fields: HashMap::new(),
             m_field_to_tag: HashMap::new(),
             m_field_to_type: HashMap::new(),
             m_components: HashMap::new(),
             m_messages: HashMap::new(),
+            m_header: Message::new("Header".to_string(), String::new(), String::new()),
+            m_trailer: Message::new("Trailer".to_string(), String::new(), String::new()),
+            m_message_defs: HashMap::new(),
+            m_header_def: Component::new("Header".to_string()),
+            m_trailer_def: Component::new("Trailer".to_string()),
+            m_issues: Vec::new(),
         }
     }
 
+    /// Returns the BeginString(8) of the FIX version, e.g. "FIX.4.4", "FIX.5.0SP2" or "FIXT.1.1".
+    pub fn get_begin_string(&self) -> &str {
+        &self.m_begin_string
+    }
+
+    /// Returns the ApplVerID(1128) of the FIX version, None for a FIXT transport dictionary.
+    pub fn get_appl_ver_id(&self) -> Option<&str> {
+        self.m_appl_ver_id.as_deref()
+    }
+
+    /// Returns true for a FIXT transport dictionary, which defines the header, trailer and
+    /// session messages used to carry application messages of other FIX versions.
+    pub fn is_transport(&self) -> bool {
+        self.m_appl_ver_id.is_none() && self.m_begin_string.starts_with("FIXT")
+    }
+
+    /// Builds the dictionary for application messages carried over a FIXT transport. The header,
+    /// trailer and session messages come from the transport dictionary, the application messages,
+    /// components and fields from the application dictionary. Where both define a field or
+    /// message the application definition is kept. The BeginString is the transport's.
+    pub fn combine(transport: &Dictionary, application: &Dictionary) -> Dictionary {
+        let mut combined = application.clone();
+        combined.m_begin_string = transport.m_begin_string.clone();
+        combined.m_header = transport.m_header.clone();
+        combined.m_trailer = transport.m_trailer.clone();
+        combined.m_header_def = transport.m_header_def.clone();
+        combined.m_trailer_def = transport.m_trailer_def.clone();
+        combined.m_issues.extend(transport.m_issues.iter().cloned());
+
+        for (tag, field) in transport.m_fix_fields.iter() {
+            if !combined.m_fix_fields.contains_key(tag) && !combined.m_field_to_tag.contains_key(field.get_name()) {
+                combined.m_fix_fields.insert(*tag, field.clone());
+                combined.m_field_to_tag.insert(field.get_name().to_string(), *tag);
+                combined.m_field_to_type.insert(field.get_name().to_string(), transport.m_field_to_type[field.get_name()].clone());
+            }
+        }
+        for (name, component) in transport.m_components.iter() {
+            combined.m_components.entry(name.clone()).or_insert_with(|| component.clone());
+        }
+        for (msgtype, message) in transport.m_messages.iter() {
+            if !combined.m_messages.contains_key(msgtype) {
+                combined.m_messages.insert(msgtype.clone(), message.clone());
+                combined.m_message_defs.insert(msgtype.clone(), transport.m_message_defs[msgtype].clone());
+            }
+        }
+        combined
+    }
+
     pub fn get_field_by_name(&self, name: &String) -> Option<&Field> {
         if let Some(tag) = self.m_field_to_tag.get(name) {
             Some(self.m_fix_fields.get(tag).unwrap())
@@ -38,151 +134,565 @@ impl Dictionary {
         }
     }
 
-    fn load_fields(&mut self, node: &roxmltree::Node) -> Result<(), ExtransError> {
-        for field in node.children() {
-            if field.has_attribute("number") && field.has_attribute("name") {
-                let tag = match i32::from_str(field.attribute("number").unwrap()) {
-                    Ok(tag) => tag,
-                    Err(e) => {
-                        return Err(ExtransError::SetupError(e.to_string()));
+    /// Looks up a field definition by tag number.
+    pub fn get_field(&self, tag: i32) -> Option<&Field> {
+        self.m_fix_fields.get(&tag)
+    }
+
+    /// Looks up a message definition by MsgType(35) value.
+    pub fn get_message(&self, msgtype: &str) -> Option<&Message> {
+        self.m_messages.get(msgtype)
+    }
+
+    /// Returns the standard header definition, fields in declared order.
+    pub fn get_header(&self) -> &Message {
+        &self.m_header
+    }
+
+    /// Returns the standard trailer definition, fields in declared order.
+    pub fn get_trailer(&self) -> &Message {
+        &self.m_trailer
+    }
+
+    pub fn is_header_field(&self, tag: i32) -> bool {
+        self.m_header.has_tag(tag)
+    }
+
+    pub fn is_trailer_field(&self, tag: i32) -> bool {
+        self.m_trailer.has_tag(tag)
+    }
+
+    pub fn iter_fields(&self) -> impl Iterator<Item = &Field> {
+        self.m_fix_fields.values()
+    }
+
+    pub fn iter_messages(&self) -> impl Iterator<Item = &Message> {
+        self.m_messages.values()
+    }
+
+    /// Checks the loaded definitions and returns every problem found, the issues recorded
+    /// while loading included. Fields referring to unknown names, undefined components and
+    /// group count fields that are not NUMINGROUP are reported for the header, trailer,
+    /// messages and components. An empty report means the dictionary is consistent.
+    pub fn validate(&self) -> Vec<DictionaryIssue> {
+        let mut issues = self.m_issues.clone();
+
+        self.validate_entries("header", &self.m_header_def, self.m_header_def.iter().as_slice(), &mut issues);
+        self.validate_entries("trailer", &self.m_trailer_def, self.m_trailer_def.iter().as_slice(), &mut issues);
+
+        let mut msgtypes: Vec<&String> = self.m_message_defs.keys().collect();
+        msgtypes.sort();
+        for msgtype in msgtypes {
+            let definition = &self.m_message_defs[msgtype];
+            let context = format!("message {}", self.m_messages[msgtype].get_name());
+            self.validate_entries(&context, definition, definition.iter().as_slice(), &mut issues);
+        }
+
+        let mut names: Vec<&String> = self.m_components.keys().collect();
+        names.sort();
+        for name in names {
+            let component = &self.m_components[name];
+            self.validate_entries(&format!("component {}", name), component, component.iter().as_slice(), &mut issues);
+        }
+
+        issues
+    }
+
+    fn validate_entries(&self, context: &str, definition: &Component, entries: &[(ComponentFieldType, String, bool)], issues: &mut Vec<DictionaryIssue>) {
+        for (cft, name, _) in entries {
+            match cft {
+                ComponentFieldType::FIELD => {
+                    if !self.m_field_to_tag.contains_key(name) {
+                        issues.push(DictionaryIssue::UnknownField { context: context.to_string(), name: name.clone() });
+                    }
+                },
+                ComponentFieldType::COMPONENT => {
+                    if !self.m_components.contains_key(name) {
+                        issues.push(DictionaryIssue::UndefinedComponent { context: context.to_string(), name: name.clone() });
                     }
-                };
-                let name = field.attribute("name").unwrap();
-                let field_type = field.attribute("type").unwrap();
+                },
+                ComponentFieldType::GROUP => {
+                    match self.m_field_to_type.get(name) {
+                        None => issues.push(DictionaryIssue::UnknownField { context: context.to_string(), name: name.clone() }),
+                        Some(field_type) if field_type != "NUMINGROUP" => {
+                            issues.push(DictionaryIssue::InvalidGroupCountType { context: context.to_string(), name: name.clone(), field_type: field_type.clone() });
+                        },
+                        Some(_) => {},
+                    }
+                    if let Some(group_fields) = definition.get_group_fields(name.clone()) {
+                        self.validate_entries(&format!("{} group {}", context, name), definition, &group_fields, issues);
+                    }
+                },
+            }
+        }
+    }
 
-                let f = Field::new(tag, String::from(name), String::from(field_type));
-                self.m_fix_fields.insert(tag, f);
+    fn missing_attribute(context: &str, element: &str, attribute: &str) -> DictionaryIssue {
+        DictionaryIssue::MissingAttribute { context: context.to_string(), element: element.to_string(), attribute: attribute.to_string() }
+    }
 
-                self.m_field_to_tag.insert(name.to_string(), tag);
-                self.m_field_to_type.insert(name.to_string(), field_type.to_string());
+    fn load_fields(&mut self, node: &roxmltree::Node) -> Result<(), ExtransError> {
+        for field in node.children().filter(|n| n.has_tag_name("field")) {
+            let name = match field.attribute("name") {
+                Some(name) => name,
+                None => {
+                    let context = format!("field {}", field.attribute("number").unwrap_or("?"));
+                    self.m_issues.push(Self::missing_attribute(&context, "field", "name"));
+                    continue;
+                }
+            };
+
+            let context = format!("field {}", name);
+            let (number, field_type) = match (field.attribute("number"), field.attribute("type")) {
+                (Some(number), Some(field_type)) => (number, field_type),
+                (None, _) => {
+                    self.m_issues.push(Self::missing_attribute(&context, "field", "number"));
+                    continue;
+                },
+                (_, None) => {
+                    self.m_issues.push(Self::missing_attribute(&context, "field", "type"));
+                    continue;
+                },
+            };
+
+            let tag = match i32::from_str(number) {
+                Ok(tag) => tag,
+                Err(_) => {
+                    self.m_issues.push(DictionaryIssue::InvalidAttribute { context, attribute: "number".to_string(), value: number.to_string() });
+                    continue;
+                }
+            };
+
+            let duplicates = self.remove_duplicates(tag, name);
+            self.m_issues.extend(duplicates);
+
+            let mut f = Field::new(tag, String::from(name), String::from(field_type));
+            for (enum_value, description) in Self::load_field_values(&field) {
+                f.add_value(enum_value, description);
             }
+            self.insert_field(f, field_type);
         }
         Ok(())
     }
 
-    fn load_component(&mut self, components: &roxmltree::Node) -> Result<(), ExtransError> {
-        for component_node in components.children() {
-            if component_node.has_attribute("name") {
-                let component_name = component_node.attribute("name").unwrap().to_string();
-                let mut component = Component::new(component_name.clone());
-                for field_node in component_node.children() {
-                    match field_node.tag_name().name() {
-                        "field" => component.add_field(field_node.attribute("name").unwrap().to_string()),
-                        "component" => component.add_component(field_node.attribute("name").unwrap().to_string()),
-                        "group" => {
-                            let mut group_fields: Vec<String> = Vec::new();
-                            for node in field_node.children() {
-                                if node.has_attribute("name") {
-                                    group_fields.push(node.attribute("name").unwrap().to_string());
-                                }
-                            }
-                            component.add_group(field_node.attribute("name").unwrap().to_string(), group_fields);
-                        },
-                        _ => {},
+    fn load_field_values(field: &roxmltree::Node) -> Vec<(String, String)> {
+        field.children()
+            .filter(|n| n.has_tag_name("value"))
+            .filter_map(|value| Some((value.attribute("enum")?.to_string(), value.attribute("description").unwrap_or("").to_string())))
+            .collect()
+    }
+
+    /// Removes the definitions a field with this tag and name replaces, returning the duplicate
+    /// tag and name issues to report.
+    fn remove_duplicates(&mut self, tag: i32, name: &str) -> Vec<DictionaryIssue> {
+        let mut issues = Vec::new();
+        if let Some(existing) = self.m_fix_fields.get(&tag) {
+            let existing_name = existing.get_name().to_string();
+            issues.push(DictionaryIssue::DuplicateTag { tag, name: name.to_string(), existing: existing_name.clone() });
+            self.m_field_to_tag.remove(&existing_name);
+            self.m_field_to_type.remove(&existing_name);
+        }
+        if let Some(existing) = self.m_field_to_tag.get(name) {
+            issues.push(DictionaryIssue::DuplicateName { name: name.to_string(), tag, existing: *existing });
+            self.m_fix_fields.remove(existing);
+        }
+        issues
+    }
+
+    fn insert_field(&mut self, field: Field, field_type: &str) {
+        self.m_field_to_tag.insert(field.get_name().to_string(), field.get_tag());
+        self.m_field_to_type.insert(field.get_name().to_string(), field_type.to_string());
+        self.m_fix_fields.insert(field.get_tag(), field);
+    }
+
+    /// Adds or overrides fields from an overlay. A field redefining an existing tag and name may
+    /// omit its number and type, a different type is reported as a conflict. Enumeration values
+    /// are merged, overlay descriptions replacing those of existing values. A field reusing the
+    /// tag or name of another field replaces it as in a base dictionary.
+    fn load_overlay_fields(&mut self, node: &roxmltree::Node, issues: &mut Vec<DictionaryIssue>) {
+        for field in node.children().filter(|n| n.has_tag_name("field")) {
+            let name = match field.attribute("name") {
+                Some(name) => name,
+                None => {
+                    let context = format!("field {}", field.attribute("number").unwrap_or("?"));
+                    issues.push(Self::missing_attribute(&context, "field", "name"));
+                    continue;
+                }
+            };
+
+            let context = format!("field {}", name);
+            let tag = match field.attribute("number").map(|number| (number, i32::from_str(number))) {
+                Some((_, Ok(tag))) => tag,
+                Some((number, Err(_))) => {
+                    issues.push(DictionaryIssue::InvalidAttribute { context, attribute: "number".to_string(), value: number.to_string() });
+                    continue;
+                },
+                None => match self.m_field_to_tag.get(name) {
+                    Some(tag) => *tag,
+                    None => {
+                        issues.push(Self::missing_attribute(&context, "field", "number"));
+                        continue;
+                    }
+                },
+            };
+
+            let existing = self.m_fix_fields.get(&tag).filter(|f| f.get_name() == name).cloned();
+            let existing_type = existing.as_ref().map(|_| self.m_field_to_type[name].clone());
+            let field_type = match (field.attribute("type"), &existing_type) {
+                (Some(field_type), _) => field_type.to_string(),
+                (None, Some(existing_type)) => existing_type.clone(),
+                (None, None) => {
+                    issues.push(Self::missing_attribute(&context, "field", "type"));
+                    continue;
+                },
+            };
+
+            let values = Self::load_field_values(&field);
+            let mut f = Field::new(tag, name.to_string(), field_type.clone());
+            match existing {
+                Some(existing) => {
+                    let existing_type = existing_type.unwrap();
+                    if existing_type != field_type {
+                        issues.push(DictionaryIssue::ConflictingFieldType { name: name.to_string(), tag, field_type: field_type.clone(), existing: existing_type });
+                    }
+                    for (value, description) in existing.iter_values() {
+                        let description = values.iter().find(|(v, _)| v == value).map(|(_, d)| d).unwrap_or(description);
+                        f.add_value(value.clone(), description.clone());
+                    }
+                    for (value, description) in values.into_iter().filter(|(v, _)| existing.get_value_description(v).is_none()) {
+                        f.add_value(value, description);
+                    }
+                },
+                None => {
+                    issues.extend(self.remove_duplicates(tag, name));
+                    for (value, description) in values {
+                        f.add_value(value, description);
                     }
+                },
+            }
+            self.insert_field(f, &field_type);
+        }
+    }
+
+    /// Ties every DATA and XMLDATA field to the LENGTH field giving its size. The length field is
+    /// found by name, e.g. RawDataLength(95) for RawData(96) or XmlDataLen(212) for XmlData(213),
+    /// falling back to a LENGTH field with the preceding tag number.
+    fn link_data_fields(&mut self) -> Vec<DictionaryIssue> {
+        let mut issues = Vec::new();
+        let mut data_tags: Vec<i32> = self.m_fix_fields.values()
+            .filter(|f| f.get_field_type().is_data())
+            .map(|f| f.get_tag())
+            .collect();
+        data_tags.sort();
+
+        for data_tag in data_tags {
+            let name = self.m_fix_fields[&data_tag].get_name().to_string();
+            let is_length = |tag: &i32| self.m_fix_fields.get(tag).map(|f| *f.get_field_type() == FieldType::LENGTH).unwrap_or(false);
+            let length_tag = [format!("{}Length", name), format!("{}Len", name)].iter()
+                .filter_map(|length_name| self.m_field_to_tag.get(length_name).cloned())
+                .chain(std::iter::once(data_tag - 1))
+                .find(is_length);
+
+            match length_tag {
+                Some(length_tag) => {
+                    self.m_fix_fields.get_mut(&data_tag).unwrap().set_length_tag(length_tag);
+                    self.m_fix_fields.get_mut(&length_tag).unwrap().set_data_tag(data_tag);
+                },
+                None => issues.push(DictionaryIssue::UnpairedDataField { name }),
+            }
+        }
+        issues
+    }
+
+    /// Reads the field, component and group children of a component, message, header or
+    /// trailer definition into the component. Groups are registered with the component as
+    /// they are found, nested groups included. Children without a name are reported and skipped.
+    fn load_component_entries(component: &mut Component, parent_node: &roxmltree::Node, context: &str, issues: &mut Vec<DictionaryIssue>) -> Vec<(ComponentFieldType, String, bool)> {
+        let mut entries: Vec<(ComponentFieldType, String, bool)> = Vec::new();
+        for node in parent_node.children().filter(|n| n.is_element()) {
+            let element = node.tag_name().name();
+            if !matches!(element, "field" | "component" | "group") {
+                continue;
+            }
+
+            let name = match node.attribute("name") {
+                Some(name) => name,
+                None => {
+                    issues.push(Self::missing_attribute(context, element, "name"));
+                    continue;
+                }
+            };
+
+            match element {
+                "field" => entries.push((ComponentFieldType::FIELD, name.to_string(), is_required(&node))),
+                "component" => entries.push((ComponentFieldType::COMPONENT, name.to_string(), is_required(&node))),
+                _ => {
+                    let group_fields = Self::load_component_entries(component, &node, &format!("{} group {}", context, name), issues);
+                    component.add_nested_group(name.to_string(), group_fields);
+                    entries.push((ComponentFieldType::GROUP, name.to_string(), is_required(&node)));
+                },
+            }
+        }
+        entries
+    }
+
+    /// Builds a component from the children of an XML definition node.
+    fn load_component_definition(name: String, node: &roxmltree::Node, context: &str, issues: &mut Vec<DictionaryIssue>) -> Component {
+        let mut component = Component::new(name);
+        let entries = Self::load_component_entries(&mut component, node, context, issues);
+        component.add_entries(entries);
+        component
+    }
+
+    /// Adds components from an overlay, merging those already defined with `Component::merge`.
+    fn load_overlay_components(&mut self, components: &roxmltree::Node, issues: &mut Vec<DictionaryIssue>) {
+        for component_node in components.children().filter(|n| n.has_tag_name("component")) {
+            let component_name = match component_node.attribute("name") {
+                Some(component_name) => component_name,
+                None => {
+                    issues.push(Self::missing_attribute("components", "component", "name"));
+                    continue;
                 }
-                self.m_components.insert(component_name, component);
+            };
+
+            let context = format!("component {}", component_name);
+            let component = Self::load_component_definition(component_name.to_string(), &component_node, &context, issues);
+            match self.m_components.get_mut(component_name) {
+                Some(existing) => existing.merge(&component),
+                None => { self.m_components.insert(component_name.to_string(), component); },
+            }
+        }
+    }
+
+    fn load_component(&mut self, components: &roxmltree::Node) -> Result<(), ExtransError> {
+        for component_node in components.children().filter(|n| n.has_tag_name("component")) {
+            match component_node.attribute("name") {
+                Some(component_name) => {
+                    let context = format!("component {}", component_name);
+                    let component = Self::load_component_definition(component_name.to_string(), &component_node, &context, &mut self.m_issues);
+                    self.m_components.insert(component_name.to_string(), component);
+                },
+                None => self.m_issues.push(Self::missing_attribute("components", "component", "name")),
             }
         }
         Ok(())
     }
 
+    /// Populates a message, or the header and trailer, from the field, component and group
+    /// children of its XML definition, expanding components recursively. The definition is
+    /// returned so that it can be validated later.
+    fn load_message_fields(&mut self, message_node: roxmltree::Node, message: &mut Message, context: &str) -> Result<Component, ExtransError> {
+        let definition = Self::load_component_definition(message.get_name().to_string(), &message_node, context, &mut self.m_issues);
+        self.resolve_component(message, &definition, true, &mut Vec::new())?;
+        Ok(definition)
+    }
+
     fn load_messages(&mut self, messages: &roxmltree::Node) -> Result<(), ExtransError> {
-        for message_node in messages.children() {
-            if message_node.has_attribute("name") && message_node.has_attribute("msgtype") && message_node.has_attribute("msgcat") {
-                let message_name = message_node.attribute("name").unwrap().to_string();
-                let message_type = message_node.attribute("msgtype").unwrap().to_string();
-                let message_cat = message_node.attribute("msgcat").unwrap().to_string();
-                
-                let mut message: Message = Message::new(message_name, message_type.clone(), message_cat);
-
-                for node in message_node.children() {
-                    if node.has_tag_name("field") {
-                        if node.has_attribute("name") {
-                            let field_name = node.attribute("name").unwrap();
-                            if let Some(field) = self.get_field_by_name(&field_name.to_string()) {
-                                message.add_field(field.clone());
-                            }
-                        }
-                    } else if node.has_tag_name("component") {
-                        if node.has_attribute("name") {
-                            let component_name = node.attribute("name").unwrap();
-                            if let Some(component) = self.m_components.get(component_name) {
-                                self.resolve_component(&mut message, component);
-                            }
-                        }
-                    } else if node.has_tag_name("group") {
-                        if node.has_attribute("name") {
-                            let group_name = node.attribute("name").unwrap();
-                            if let Some(field) = self.get_field_by_name(&group_name.to_string()) {
-                                let mut group: Group = Group::new(field.get_tag());
-                                for field_node in node.children() {
-                                    if field_node.has_tag_name("field") && field_node.has_attribute("name") {
-                                        let field_name = field_node.attribute("name").unwrap().to_string();
-                                        if let Some(field) = self.get_field_by_name(&field_name) {
-                                            group.add_field(field.clone());
-                                        }
-                                    }
-                                }
-                                message.add_group(group);
-                            }
-                        }
-                    }
+        for message_node in messages.children().filter(|n| n.has_tag_name("message")) {
+            let context = format!("message {}", message_node.attribute("name").unwrap_or("?"));
+            let attributes = (message_node.attribute("name"), message_node.attribute("msgtype"), message_node.attribute("msgcat"));
+            let (message_name, message_type, message_cat) = match attributes {
+                (Some(name), Some(msgtype), Some(msgcat)) => (name.to_string(), msgtype.to_string(), msgcat.to_string()),
+                (name, msgtype, _) => {
+                    let attribute = if name.is_none() { "name" } else if msgtype.is_none() { "msgtype" } else { "msgcat" };
+                    self.m_issues.push(Self::missing_attribute(&context, "message", attribute));
+                    continue;
+                }
+            };
+
+            let mut message: Message = Message::new(message_name, message_type.clone(), message_cat);
+            let definition = self.load_message_fields(message_node, &mut message, &context)?;
+            log::debug!("loaded message {} ({}) with {} fields", message.get_name(), message.get_type(), message.iter_fields().count());
+            log::trace!("message: {}", message);
+            self.m_messages.insert(message_type.clone(), message);
+            self.m_message_defs.insert(message_type, definition);
+        }
+        Ok(())
+    }
+
+    /// Adds messages from an overlay. A message type already defined has the overlay definition
+    /// merged into its own and may omit msgcat, a different name is reported as a conflict.
+    fn load_overlay_messages(&mut self, messages: &roxmltree::Node, issues: &mut Vec<DictionaryIssue>) {
+        for message_node in messages.children().filter(|n| n.has_tag_name("message")) {
+            let context = format!("message {}", message_node.attribute("name").unwrap_or("?"));
+            let (message_name, message_type) = match (message_node.attribute("name"), message_node.attribute("msgtype")) {
+                (Some(name), Some(msgtype)) => (name.to_string(), msgtype.to_string()),
+                (name, _) => {
+                    issues.push(Self::missing_attribute(&context, "message", if name.is_none() { "name" } else { "msgtype" }));
+                    continue;
+                }
+            };
+
+            let definition = Self::load_component_definition(message_name.clone(), &message_node, &context, issues);
+            let message_cat = match (message_node.attribute("msgcat"), self.m_messages.get(&message_type)) {
+                (Some(msgcat), _) => msgcat.to_string(),
+                (None, Some(existing)) => existing.get_category().to_string(),
+                (None, None) => {
+                    issues.push(Self::missing_attribute(&context, "message", "msgcat"));
+                    continue;
+                },
+            };
+
+            if let Some(existing) = self.m_messages.get(&message_type) {
+                if existing.get_name() != message_name {
+                    issues.push(DictionaryIssue::ConflictingMessageName { msgtype: message_type.clone(), name: message_name.clone(), existing: existing.get_name().to_string() });
                 }
-                println!("message: {}", message);
-                self.m_messages.insert(message_type.clone(), message);
             }
+            match self.m_message_defs.get_mut(&message_type) {
+                Some(existing) => existing.merge(&definition),
+                None => { self.m_message_defs.insert(message_type.clone(), definition); },
+            }
+            self.m_messages.insert(message_type.clone(), Message::new(message_name, message_type, message_cat));
+        }
+    }
+
+    /// Rebuilds the header, trailer and every message from their retained definitions, so that
+    /// overlaid fields, components and messages are expanded into the resolved layouts.
+    fn resolve_messages(&mut self) -> Result<(), ExtransError> {
+        let mut header = Message::new("Header".to_string(), String::new(), String::new());
+        self.resolve_component(&mut header, &self.m_header_def, true, &mut Vec::new())?;
+        let mut trailer = Message::new("Trailer".to_string(), String::new(), String::new());
+        self.resolve_component(&mut trailer, &self.m_trailer_def, true, &mut Vec::new())?;
+
+        let mut messages = HashMap::new();
+        for (msgtype, definition) in self.m_message_defs.iter() {
+            let existing = &self.m_messages[msgtype];
+            let mut message = Message::new(existing.get_name().to_string(), msgtype.clone(), existing.get_category().to_string());
+            self.resolve_component(&mut message, definition, true, &mut Vec::new())?;
+            log::trace!("message: {}", message);
+            messages.insert(msgtype.clone(), message);
         }
+
+        self.m_header = header;
+        self.m_trailer = trailer;
+        self.m_messages = messages;
         Ok(())
     }
 
-    fn resolve_component(&self, message: &mut Message, component: &Component) {
-        for (cft, field_name) in component.iter() {
+    /// Expands a component into a message or group entry. Fields of a component are only
+    /// mandatory when the component itself is required where it is referenced. `path` holds
+    /// the names of the components currently being expanded and is used to detect cycles.
+    fn resolve_component<T: FieldContainer>(&self, target: &mut T, component: &Component, component_required: bool, path: &mut Vec<String>) -> Result<(), ExtransError> {
+        for (cft, field_name, required) in component.iter() {
+            let required = component_required && *required;
             match cft {
                 ComponentFieldType::FIELD => {
                     if let Some(field) = self.get_field_by_name(&field_name.to_string()) {
-                        message.add_field(field.clone());
+                        target.add_field(field.clone(), required);
                     }
                 },
                 ComponentFieldType::GROUP => {
                     if let Some(field) = self.get_field_by_name(&field_name.to_string()) {
-                        let mut group: Group = Group::new(field.get_tag());
-                        if let Some(group_fields) = component.get_group_fields(field_name.to_string()) {
-                            for gf in group_fields {
-                                if let Some(field) = self.get_field_by_name(&gf.to_string()) {
-                                    group.add_field(field.clone());
-                                }
-                            }
-                        }
-                        message.add_group(group);
+                        let group = self.resolve_component_group(component, field, path)?;
+                        target.add_group(field.clone(), group, required);
                     }
                 },
                 ComponentFieldType::COMPONENT => {
-                    if let Some(lookup) = self.m_components.get(field_name) {
-                        self.resolve_component(message, lookup);
-                    }
+                    self.resolve_component_reference(target, field_name, required, path)?;
                 }
             }
-        } 
+        }
+        Ok(())
     }
 
-    pub fn load(&mut self, filename: &str) -> Result<(), ExtransError> {
+    /// Expands a component referenced by name, failing if the reference is cyclic.
+    fn resolve_component_reference<T: FieldContainer>(&self, target: &mut T, name: &String, required: bool, path: &mut Vec<String>) -> Result<(), ExtransError> {
+        if path.contains(name) {
+            return Err(ExtransError::SetupError(format!("cyclic component reference: {} -> {}", path.join(" -> "), name)));
+        }
+
+        if let Some(component) = self.m_components.get(name) {
+            path.push(name.clone());
+            self.resolve_component(target, component, required, path)?;
+            path.pop();
+        }
+        Ok(())
+    }
+
+    /// Builds a group defined within a component, nested groups and components included.
+    fn resolve_component_group(&self, component: &Component, field: &Field, path: &mut Vec<String>) -> Result<Group, ExtransError> {
+        let mut group: Group = Group::new(field.get_tag());
+        if let Some(group_fields) = component.get_group_fields(field.get_name().to_string()) {
+            for (cft, name, required) in group_fields {
+                match cft {
+                    ComponentFieldType::FIELD => {
+                        if let Some(field) = self.get_field_by_name(&name) {
+                            group.add_field(field.clone(), required);
+                        }
+                    },
+                    ComponentFieldType::GROUP => {
+                        if let Some(field) = self.get_field_by_name(&name) {
+                            let nested = self.resolve_component_group(component, field, path)?;
+                            group.add_group(field.clone(), nested, required);
+                        }
+                    },
+                    ComponentFieldType::COMPONENT => {
+                        self.resolve_component_reference(&mut group, &name, required, path)?;
+                    },
+                }
+            }
+        }
+        Ok(group)
+    }
+
+    fn read_file(filename: &str) -> Result<String, ExtransError> {
         let path = Path::new(filename);
         if !path.exists() {
-            return Err(ExtransError::SetupError(format!("FIX Dictionary '{}' does not exist.", filename).into()));
+            return Err(ExtransError::SetupError(format!("FIX Dictionary '{}' does not exist.", filename)));
         }
 
         let mut file = File::open(path)?;
         let mut contents = String::new();
         file.read_to_string(&mut contents)?;
+        Ok(contents)
+    }
+
+    /// Loads a dictionary from a QuickFIX style XML file, or from a FIX Orchestra repository
+    /// when the root element is `repository`. Both formats produce the same definitions.
+    pub fn load(&mut self, filename: &str) -> Result<(), ExtransError> {
+        let contents = Self::read_file(filename)?;
+        self.load_xml(filename, &contents)
+    }
 
-        let doc = match roxmltree::Document::parse(&contents) {
+    fn load_xml(&mut self, filename: &str, contents: &str) -> Result<(), ExtransError> {
+        let doc = match roxmltree::Document::parse(contents) {
             Ok(doc) => doc,
             Err(e) => { 
                 return Err(ExtransError::SetupError(e.to_string()));
             }
         };
 
+        log::debug!("loading FIX dictionary '{}'", filename);
+
+        let root = doc.root_element();
+        if root.has_tag_name("repository") {
+            self.load_repository(orchestra::read_repository(&root)?)?;
+        } else {
+            self.load_quickfix(&doc)?;
+        }
+
+        log::info!("loaded FIX dictionary '{}' ({}): {} fields, {} components, {} messages",
+            filename, self.m_begin_string, self.m_fix_fields.len(), self.m_components.len(), self.m_messages.len());
+        if !self.m_issues.is_empty() {
+            log::warn!("FIX dictionary '{}' has {} definition problems, see Dictionary::validate", filename, self.m_issues.len());
+        }
+
+        Ok(())
+    }
+
+    fn load_quickfix(&mut self, doc: &roxmltree::Document) -> Result<(), ExtransError> {
+        let root = doc.root_element();
+        if root.has_tag_name("fix") {
+            self.m_begin_string = format!("{}.{}.{}",
+                root.attribute("type").unwrap_or("FIX"),
+                root.attribute("major").unwrap_or("4"),
+                root.attribute("minor").unwrap_or("4"));
+            if let Some(servicepack) = root.attribute("servicepack").filter(|sp| *sp != "0") {
+                self.m_begin_string.push_str(&format!("SP{}", servicepack));
+            }
+            self.m_appl_ver_id = begin_string_to_appl_ver_id(&self.m_begin_string).map(|id| id.to_string());
+        }
+
         for node in doc.descendants() {
             if node.has_tag_name("components") {
                 self.load_component(&node).map_err(|e| ExtransError::SetupError(e.to_string()))?;
@@ -191,13 +701,233 @@ impl Dictionary {
             }
         }
 
+        let unpaired = self.link_data_fields();
+        self.m_issues.extend(unpaired);
+
         for node in doc.descendants() {
             if node.has_tag_name("messages") {
                 self.load_messages(&node).map_err(|e| ExtransError::SetupError(e.to_string()))?;
+            } else if node.has_tag_name("header") {
+                let mut header = Message::new("Header".to_string(), String::new(), String::new());
+                self.m_header_def = self.load_message_fields(node, &mut header, "header")?;
+                self.m_header = header;
+            } else if node.has_tag_name("trailer") {
+                let mut trailer = Message::new("Trailer".to_string(), String::new(), String::new());
+                self.m_trailer_def = self.load_message_fields(node, &mut trailer, "trailer")?;
+                self.m_trailer = trailer;
             }
         }
+        Ok(())
+    }
 
+    /// Builds the dictionary from the definitions read from an Orchestra repository.
+    fn load_repository(&mut self, repository: Repository) -> Result<(), ExtransError> {
+        self.m_begin_string = repository.m_begin_string;
+        self.m_appl_ver_id = begin_string_to_appl_ver_id(&self.m_begin_string).map(|id| id.to_string());
+        self.m_issues.extend(repository.m_issues);
+
+        for (field, field_type) in repository.m_fields {
+            let duplicates = self.remove_duplicates(field.get_tag(), field.get_name());
+            self.m_issues.extend(duplicates);
+            self.insert_field(field, &field_type);
+        }
+        for component in repository.m_components {
+            self.m_components.insert(component.get_name().to_string(), component);
+        }
+
+        let unpaired = self.link_data_fields();
+        self.m_issues.extend(unpaired);
+
+        self.m_header_def = repository.m_header;
+        self.m_trailer_def = repository.m_trailer;
+        for (message, definition) in repository.m_messages {
+            log::debug!("loaded message {} ({})", message.get_name(), message.get_type());
+            self.m_message_defs.insert(message.get_type().to_string(), definition);
+            self.m_messages.insert(message.get_type().to_string(), message);
+        }
+        self.resolve_messages()
+    }
+
+    /// Applies an overlay file on top of the loaded dictionary, e.g. the user defined tags and
+    /// extra message fields of a venue. The overlay uses the dictionary XML format and may hold
+    /// only the definitions it adds or changes: fields, enumeration values, components, messages
+    /// and header or trailer entries. Existing definitions are merged with the overlay's, which
+    /// takes precedence, and the messages are then resolved again.
+    ///
+    /// Returns the issues raised by the overlay, conflicting field types and message names
+    /// included. They are also reported by `validate`. If the overlay cannot be applied, e.g.
+    /// because it introduces a cyclic component reference, the dictionary is left unchanged.
+    pub fn load_overlay(&mut self, filename: &str) -> Result<Vec<DictionaryIssue>, ExtransError> {
+        let contents = Self::read_file(filename)?;
+        let doc = roxmltree::Document::parse(&contents).map_err(|e| ExtransError::SetupError(e.to_string()))?;
+
+        log::debug!("loading FIX dictionary overlay '{}' onto {}", filename, self.m_begin_string);
+
+        let mut overlaid = self.clone();
+        let mut issues = Vec::new();
+        for node in doc.descendants() {
+            if node.has_tag_name("components") {
+                overlaid.load_overlay_components(&node, &mut issues);
+            } else if node.has_tag_name("fields") {
+                overlaid.load_overlay_fields(&node, &mut issues);
+            }
+        }
+
+        let unpaired = overlaid.link_data_fields();
+        overlaid.m_issues.retain(|issue| !matches!(issue, DictionaryIssue::UnpairedDataField { .. }));
+        issues.extend(unpaired.iter().filter(|issue| !self.m_issues.contains(issue)).cloned());
+        overlaid.m_issues.extend(unpaired);
+
+        for node in doc.descendants() {
+            if node.has_tag_name("messages") {
+                overlaid.load_overlay_messages(&node, &mut issues);
+            } else if node.has_tag_name("header") {
+                let definition = Self::load_component_definition("Header".to_string(), &node, "header", &mut issues);
+                overlaid.m_header_def.merge(&definition);
+            } else if node.has_tag_name("trailer") {
+                let definition = Self::load_component_definition("Trailer".to_string(), &node, "trailer", &mut issues);
+                overlaid.m_trailer_def.merge(&definition);
+            }
+        }
+
+        overlaid.resolve_messages()?;
+        overlaid.m_issues.extend(issues.iter().cloned());
+        *self = overlaid;
+
+        log::info!("loaded FIX dictionary overlay '{}' ({}): {} fields, {} components, {} messages",
+            filename, self.m_begin_string, self.m_fix_fields.len(), self.m_components.len(), self.m_messages.len());
+        for issue in issues.iter() {
+            log::warn!("FIX dictionary overlay '{}': {}", filename, issue);
+        }
+
+        Ok(issues)
+    }
+
+    /// Loads a dictionary from a binary cache of the XML file `filename`, e.g. "FIX50SP2.xml"
+    /// cached as "FIX50SP2.xml.cache". The cache holds the fully resolved dictionary, components
+    /// expanded and groups nested, so loading it skips XML parsing and component resolution.
+    ///
+    /// The cache records the size and a hash of the XML it was built from. If it is missing,
+    /// unreadable or the XML has changed since, the XML is loaded instead and the cache is
+    /// rewritten. Failing to write the cache is logged and does not fail the load.
+    pub fn load_cached(&mut self, filename: &str, cache_filename: &str) -> Result<(), ExtransError> {
+        let contents = Self::read_file(filename)?;
+        let source_len = contents.len() as u64;
+        let source_hash = cache::fingerprint(contents.as_bytes());
+
+        if Path::new(cache_filename).exists() {
+            match self.read_cache(cache_filename, source_len, source_hash) {
+                Ok(true) => {
+                    log::info!("loaded FIX dictionary '{}' ({}) from cache '{}'", filename, self.m_begin_string, cache_filename);
+                    return Ok(());
+                },
+                Ok(false) => log::info!("FIX dictionary cache '{}' is out of date, reloading '{}'", cache_filename, filename),
+                Err(e) => log::warn!("ignoring FIX dictionary cache '{}': {}", cache_filename, e),
+            }
+            *self = Dictionary::new();
+        }
+
+        self.load_xml(filename, &contents)?;
+        if let Err(e) = self.write_cache(cache_filename, source_len, source_hash) {
+            log::warn!("failed to write FIX dictionary cache '{}': {}", cache_filename, e);
+        }
         Ok(())
+    }
 
+    /// Writes the dictionary to a binary cache for the XML file `filename`, see `load_cached`.
+    pub fn save_cache(&self, filename: &str, cache_filename: &str) -> Result<(), ExtransError> {
+        let contents = Self::read_file(filename)?;
+        self.write_cache(cache_filename, contents.len() as u64, cache::fingerprint(contents.as_bytes()))
     }
-}
\ No newline at end of file
+
+    fn write_cache(&self, cache_filename: &str, source_len: u64, source_hash: u64) -> Result<(), ExtransError> {
+        let mut writer = CacheWriter::new(source_len, source_hash);
+        writer.write_str(&self.m_begin_string);
+        writer.write_opt_str(self.m_appl_ver_id.as_deref());
+
+        let mut tags: Vec<&i32> = self.m_fix_fields.keys().collect();
+        tags.sort();
+        writer.write_len(tags.len());
+        for tag in tags {
+            let field = &self.m_fix_fields[tag];
+            writer.write_field(field, &self.m_field_to_type[field.get_name()]);
+        }
+
+        let mut names: Vec<&String> = self.m_components.keys().collect();
+        names.sort();
+        writer.write_len(names.len());
+        for name in names {
+            writer.write_component(name, &self.m_components[name]);
+        }
+
+        let mut msgtypes: Vec<&String> = self.m_messages.keys().collect();
+        msgtypes.sort();
+        writer.write_len(msgtypes.len());
+        for msgtype in msgtypes {
+            let message = &self.m_messages[msgtype];
+            writer.write_message(message);
+            writer.write_component(message.get_name(), &self.m_message_defs[msgtype]);
+        }
+
+        writer.write_message(&self.m_header);
+        writer.write_component("Header", &self.m_header_def);
+        writer.write_message(&self.m_trailer);
+        writer.write_component("Trailer", &self.m_trailer_def);
+
+        writer.write_len(self.m_issues.len());
+        for issue in self.m_issues.iter() {
+            writer.write_issue(issue);
+        }
+
+        std::fs::write(cache_filename, writer.into_bytes())?;
+        log::debug!("wrote FIX dictionary cache '{}'", cache_filename);
+        Ok(())
+    }
+
+    /// Replaces the dictionary with the content of a cache, returning false without changing
+    /// it if the cache was built from a different source.
+    fn read_cache(&mut self, cache_filename: &str, source_len: u64, source_hash: u64) -> Result<bool, ExtransError> {
+        let data = std::fs::read(cache_filename)?;
+        let mut reader = match CacheReader::new(&data, source_len, source_hash)? {
+            Some(reader) => reader,
+            None => return Ok(false),
+        };
+
+        let mut dictionary = Dictionary::new();
+        dictionary.m_begin_string = reader.read_string()?;
+        dictionary.m_appl_ver_id = reader.read_opt_string()?;
+
+        for _ in 0..reader.read_len()? {
+            let (field, field_type) = reader.read_field()?;
+            dictionary.insert_field(field, &field_type);
+        }
+
+        for _ in 0..reader.read_len()? {
+            let component = reader.read_component()?;
+            dictionary.m_components.insert(component.get_name().to_string(), component);
+        }
+
+        for _ in 0..reader.read_len()? {
+            let message = reader.read_message(&dictionary.m_fix_fields)?;
+            let definition = reader.read_component()?;
+            dictionary.m_message_defs.insert(message.get_type().to_string(), definition);
+            dictionary.m_messages.insert(message.get_type().to_string(), message);
+        }
+
+        dictionary.m_header = reader.read_message(&dictionary.m_fix_fields)?;
+        dictionary.m_header_def = reader.read_component()?;
+        dictionary.m_trailer = reader.read_message(&dictionary.m_fix_fields)?;
+        dictionary.m_trailer_def = reader.read_component()?;
+
+        for _ in 0..reader.read_len()? {
+            dictionary.m_issues.push(reader.read_issue()?);
+        }
+
+        if !reader.is_at_end() {
+            return Err(ExtransError::SetupError(format!("corrupt FIX dictionary cache '{}': trailing data", cache_filename)));
+        }
+
+        *self = dictionary;
+        Ok(true)
+    }
+}
@@ -0,0 +1,45 @@
+use std::fmt;
+
+/// A problem found in a FIX dictionary definition. Issues do not prevent the dictionary from
+/// loading, the offending definition is skipped, but they are reported by `Dictionary::validate`
+/// so that broken or incomplete dictionaries can be detected up front.
+#[derive(Clone, Debug, PartialEq)]
+pub enum DictionaryIssue {
+    /// A message, component or group refers to a field name that is not defined.
+    UnknownField { context: String, name: String },
+    /// A message, component or group refers to a component that is not defined.
+    UndefinedComponent { context: String, name: String },
+    /// Two field definitions share a tag number, the later definition is used.
+    DuplicateTag { tag: i32, name: String, existing: String },
+    /// Two field definitions share a name, the later definition is used.
+    DuplicateName { name: String, tag: i32, existing: i32 },
+    /// A group count field is not of type NUMINGROUP.
+    InvalidGroupCountType { context: String, name: String, field_type: String },
+    /// A DATA or XMLDATA field has no LENGTH field giving its size.
+    UnpairedDataField { name: String },
+    /// An element is missing a mandatory attribute and has been skipped.
+    MissingAttribute { context: String, element: String, attribute: String },
+    /// An attribute value could not be parsed and the element has been skipped.
+    InvalidAttribute { context: String, attribute: String, value: String },
+    /// An overlay changes the type of an existing field, the overlay type is used.
+    ConflictingFieldType { name: String, tag: i32, field_type: String, existing: String },
+    /// An overlay defines an existing message type under another name, the overlay name is used.
+    ConflictingMessageName { msgtype: String, name: String, existing: String },
+}
+
+impl fmt::Display for DictionaryIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DictionaryIssue::UnknownField { context, name } => write!(f, "{}: unknown field '{}'", context, name),
+            DictionaryIssue::UndefinedComponent { context, name } => write!(f, "{}: undefined component '{}'", context, name),
+            DictionaryIssue::DuplicateTag { tag, name, existing } => write!(f, "field {}: tag {} is already defined by field {}", name, tag, existing),
+            DictionaryIssue::DuplicateName { name, tag, existing } => write!(f, "field {}: tag {} redefines the name already used by tag {}", name, tag, existing),
+            DictionaryIssue::InvalidGroupCountType { context, name, field_type } => write!(f, "{}: group count field '{}' is of type {} not NUMINGROUP", context, name, field_type),
+            DictionaryIssue::UnpairedDataField { name } => write!(f, "field {}: no LENGTH field gives the size of the data field", name),
+            DictionaryIssue::MissingAttribute { context, element, attribute } => write!(f, "{}: {} element is missing the '{}' attribute", context, element, attribute),
+            DictionaryIssue::InvalidAttribute { context, attribute, value } => write!(f, "{}: invalid {} attribute '{}'", context, attribute, value),
+            DictionaryIssue::ConflictingFieldType { name, tag, field_type, existing } => write!(f, "field {}: tag {} changes type from {} to {}", name, tag, existing, field_type),
+            DictionaryIssue::ConflictingMessageName { msgtype, name, existing } => write!(f, "message {}: type '{}' is already defined by message {}", name, msgtype, existing),
+        }
+    }
+}
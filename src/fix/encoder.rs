@@ -0,0 +1,174 @@
+use std::collections::HashSet;
+use std::io::Write;
+
+use crate::ExtransError;
+use crate::ido::{Ido, IdoItem, IdoItemType, IdoKeyT};
+
+use super::dictionary::Dictionary;
+use super::field::{Field, FieldType};
+use super::group::Group;
+use super::message::Message;
+
+pub(crate) const SOH: u8 = 0x01;
+
+pub(crate) const BEGIN_STRING: IdoKeyT = 8;
+pub(crate) const BODY_LENGTH: IdoKeyT = 9;
+pub(crate) const MSG_TYPE: IdoKeyT = 35;
+pub(crate) const CHECK_SUM: IdoKeyT = 10;
+
+pub(crate) fn format_value(tag: IdoKeyT, item: &IdoItem, field: Option<&Field>) -> Result<String, ExtransError> {
+    match item.get_type() {
+        IdoItemType::STRING => Ok(item.m_string.clone()),
+        IdoItemType::INTEGER => match field.map(|f| f.get_field_type()) {
+            Some(FieldType::BOOLEAN) => Ok(if item.m_integer != 0 { "Y" } else { "N" }.to_string()),
+            _ => Ok(item.m_integer.to_string()),
+        },
+        IdoItemType::FLOAT if item.m_float.is_finite() => Ok(item.m_float.to_string()),
+        IdoItemType::FLOAT => Err(ExtransError::EncodeError(format!("tag {} has non-finite value {}", tag, item.m_float))),
+        IdoItemType::DATETIME => match field {
+            Some(field) => Ok(field.get_field_type().format_datetime(&item.m_datetime)),
+            None => Ok(FieldType::UTCTIMESTAMP.format_datetime(&item.m_datetime)),
+        },
+        IdoItemType::BYTES => Ok(String::from_utf8_lossy(&item.m_bytes).into_owned()),
+        IdoItemType::ARRAY => Err(ExtransError::EncodeError(format!("tag {} is not a repeating group", tag))),
+    }
+}
+
+fn write_field(buffer: &mut Vec<u8>, tag: IdoKeyT, value: &[u8]) {
+    write!(buffer, "{}=", tag).unwrap();
+    buffer.extend_from_slice(value);
+    buffer.push(SOH);
+}
+
+/// Writes a field other than a DATA or XMLDATA field, whose value may not hold the SOH delimiter.
+fn write_item(buffer: &mut Vec<u8>, dictionary: &Dictionary, tag: IdoKeyT, item: &IdoItem) -> Result<(), ExtransError> {
+    let formatted;
+    let value = if item.get_type() == IdoItemType::BYTES {
+        item.m_bytes.as_slice()
+    } else {
+        formatted = format_value(tag, item, dictionary.get_field(tag))?;
+        formatted.as_bytes()
+    };
+
+    if value.contains(&SOH) {
+        return Err(ExtransError::EncodeError(format!("tag {} value contains the SOH delimiter", tag)));
+    }
+    write_field(buffer, tag, value);
+    Ok(())
+}
+
+/// Writes a field of an Ido. A DATA or XMLDATA field is preceded by its LENGTH field, the length
+/// being taken from the data itself, and a LENGTH field whose data field is present in the Ido is
+/// left for the data field to write.
+fn write_ido_field(buffer: &mut Vec<u8>, dictionary: &Dictionary, ido: &Ido, tag: IdoKeyT, item: &IdoItem) -> Result<(), ExtransError> {
+    let field = dictionary.get_field(tag);
+    if field.and_then(|f| f.get_data_tag()).map(|data_tag| ido.contains(&data_tag)).unwrap_or(false) {
+        return Ok(());
+    }
+
+    if let Some(length_tag) = field.and_then(|f| f.get_length_tag()) {
+        let value = match item.get_type() {
+            IdoItemType::BYTES => item.m_bytes.as_slice(),
+            IdoItemType::STRING => item.m_string.as_bytes(),
+            _ => return Err(ExtransError::EncodeError(format!("data field {} must hold bytes or a string", tag))),
+        };
+        write_field(buffer, length_tag, value.len().to_string().as_bytes());
+        write_field(buffer, tag, value);
+        return Ok(());
+    }
+
+    write_item(buffer, dictionary, tag, item)
+}
+
+fn write_group(buffer: &mut Vec<u8>, dictionary: &Dictionary, group: &Group, entries: &[Ido]) -> Result<(), ExtransError> {
+    write_field(buffer, group.get_parent_tag(), entries.len().to_string().as_bytes());
+
+    for entry in entries {
+        if !entry.keys().any(|tag| group.is_first_tag(*tag)) {
+            return Err(ExtransError::EncodeError(format!("group {} entry is missing its delimiter field", group.get_parent_tag())));
+        }
+
+        for field in group.iter_fields() {
+            if let Some(item) = entry.get_item_ref(&field.get_tag()) {
+                match (group.get_group(field.get_tag()), item.get_type()) {
+                    (Some(nested), IdoItemType::ARRAY) => write_group(buffer, dictionary, nested, &item.m_array)?,
+                    _ => write_ido_field(buffer, dictionary, entry, field.get_tag(), item)?,
+                }
+            }
+        }
+
+        let mut extra: Vec<IdoKeyT> = entry.keys().filter(|tag| !group.has_tag(**tag)).cloned().collect();
+        extra.sort();
+        for tag in extra {
+            write_ido_field(buffer, dictionary, entry, tag, entry.get_item_ref(&tag).unwrap())?;
+        }
+    }
+    Ok(())
+}
+
+/// Writes the fields of the Ido defined by a message, header or trailer definition in declared order.
+fn write_section(buffer: &mut Vec<u8>, dictionary: &Dictionary, section: &Message, ido: &Ido, written: &mut HashSet<IdoKeyT>) -> Result<(), ExtransError> {
+    for field in section.iter_fields() {
+        let tag = field.get_tag();
+        if written.contains(&tag) {
+            continue;
+        }
+
+        if let Some(item) = ido.get_item_ref(&tag) {
+            match (section.get_group(tag), item.get_type()) {
+                (Some(group), IdoItemType::ARRAY) => write_group(buffer, dictionary, group, &item.m_array)?,
+                _ => write_ido_field(buffer, dictionary, ido, tag, item)?,
+            }
+            written.insert(tag);
+        }
+    }
+    Ok(())
+}
+
+/// Encodes an Ido keyed by tag number into a SOH delimited FIX tag=value message.
+///
+/// BeginString(8), BodyLength(9), MsgType(35) and CheckSum(10) are always generated, any values
+/// held in the Ido for these tags are ignored. The remaining fields are written in the order the
+/// dictionary header, message and trailer definitions declare them. Fields the dictionary does
+/// not define for the message are placed after the body fields, in ascending tag order.
+/// The LENGTH field of a DATA or XMLDATA field is always generated from the data.
+pub(crate) fn encode(dictionary: &Dictionary, ido: &Ido, msgtype: &str) -> Result<Vec<u8>, ExtransError> {
+    let message = match dictionary.get_message(msgtype) {
+        Some(message) => message,
+        None => return Err(ExtransError::EncodeError(format!("message type '{}' is not defined in the dictionary", msgtype))),
+    };
+
+    let mut written: HashSet<IdoKeyT> = HashSet::from([BEGIN_STRING, BODY_LENGTH, MSG_TYPE, CHECK_SUM]);
+    let mut body: Vec<u8> = Vec::with_capacity(256);
+
+    write_field(&mut body, MSG_TYPE, msgtype.as_bytes());
+
+    write_section(&mut body, dictionary, dictionary.get_header(), ido, &mut written)?;
+    write_section(&mut body, dictionary, message, ido, &mut written)?;
+
+    let mut extra: Vec<IdoKeyT> = ido.keys()
+        .filter(|tag| !written.contains(tag) && !dictionary.is_trailer_field(**tag))
+        .cloned()
+        .collect();
+    extra.sort();
+    for tag in extra {
+        write_ido_field(&mut body, dictionary, ido, tag, ido.get_item_ref(&tag).unwrap())?;
+    }
+
+    write_section(&mut body, dictionary, dictionary.get_trailer(), ido, &mut written)?;
+
+    let mut buffer: Vec<u8> = Vec::with_capacity(body.len() + 32);
+    write_field(&mut buffer, BEGIN_STRING, dictionary.get_begin_string().as_bytes());
+    write_field(&mut buffer, BODY_LENGTH, body.len().to_string().as_bytes());
+    buffer.extend_from_slice(&body);
+
+    let checksum = checksum(&buffer);
+    write_field(&mut buffer, CHECK_SUM, format!("{:03}", checksum).as_bytes());
+
+    Ok(buffer)
+}
+
+/// Calculates the FIX CheckSum(10) value, the sum of every byte modulo 256.
+pub(crate) fn checksum(data: &[u8]) -> u32 {
+    data.iter().fold(0u32, |sum, b| sum.wrapping_add(*b as u32)) % 256
+}
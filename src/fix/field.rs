@@ -1,35 +1,286 @@
-/// The `FIXFieldType` enum represents different field types used in the FIX (Financial Information eXchange) protocol.
-/// FIX fields can have various data types, and this enum provides a clear way to represent them. 
-/// Some of the types have been normalised where underlying data type can be captured by a single type.
-///
-/// # Variants
-///
-/// - `INTEGER`: Represents a 32-bit signed integer. INT, LENGTH and DAYOFMONTH have been mapped to INTEGER.
-/// - `DOUBLE`: Represents a double-precision floating-point number. PRICE, AMT, FLOAT, PRIECOFFSET, QTY have been mapped to DOUBLE.
-/// - `STRING`: Represents a sequence of characters.
-/// - `UTCTIMESTAMP`: Represents a timestamp in the UTC (Coordinated Universal Time) format.
-/// - `MSTIMESTAMP`: Represents a timestamp with milliseconds.
-/// - `UTCDATE`: Represents a date in the UTC format.
-/// - `UTCTIMEONLY`: Represents a time in the UTC format.
-/// - `MONTHYEAR`: Represents a month and year.
-/// - `LOCALMKTDATE`: Represents a date in the local market's timezone.
-///
-/// This enum is not intended to be used elsewhere but will serve as a performance/convenience mechanism for 
-/// field setters/getters.
-
 use std::fmt;
 
-#[derive(Clone)]
-enum FieldType {
-    INTEGER,
-    DOUBLE,
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, TimeZone, Utc};
+
+/// The `FieldType` enum represents the data types used by fields in the FIX (Financial Information eXchange)
+/// protocol, one variant per FIX datatype as named by the dictionary `type` attribute. Types the enum does
+/// not know are treated as STRING.
+///
+/// The type decides how a field value is parsed into and formatted from an `Ido`:
+///
+/// - INT, LENGTH, NUMINGROUP, SEQNUM, TAGNUM, DAYOFMONTH and the RESERVED ranges are held as integers.
+///   LENGTH, NUMINGROUP and SEQNUM may not be negative, TAGNUM must be positive, DAYOFMONTH is 1 to 31.
+/// - FLOAT, QTY, PRICE, PRICEOFFSET, AMT and PERCENTAGE are held as floating-point numbers.
+/// - UTCTIMESTAMP, MSTIMESTAMP, TZTIMESTAMP, UTCDATEONLY, UTCDATE and LOCALMKTDATE are held as UTC date
+///   and times. TZTIMESTAMP values are converted from their offset to UTC.
+/// - BOOLEAN is held as "Y" or "N", an integer value is written as "Y" when non zero.
+/// - CHAR is a single character, MULTIPLECHARVALUE a space separated list of single characters.
+/// - CURRENCY, COUNTRY and LANGUAGE are 3, 2 and 2 character codes.
+/// - DATA and XMLDATA hold raw bytes whose size is given by the companion LENGTH field, see
+///   `Field::get_length_tag`.
+/// - Everything else is held as a string.
+#[derive(Clone, Debug, PartialEq)]
+pub enum FieldType {
+    INT,
+    LENGTH,
+    NUMINGROUP,
+    SEQNUM,
+    TAGNUM,
+    DAYOFMONTH,
+    FLOAT,
+    QTY,
+    PRICE,
+    PRICEOFFSET,
+    AMT,
+    PERCENTAGE,
+    CHAR,
+    BOOLEAN,
     STRING,
+    MULTIPLECHARVALUE,
+    MULTIPLEVALUESTRING,
+    MULTIPLESTRINGVALUE,
+    CURRENCY,
+    EXCHANGE,
+    COUNTRY,
+    LANGUAGE,
+    DATA,
+    XMLDATA,
     UTCTIMESTAMP,
     MSTIMESTAMP,
-    UTCDATE,
+    TZTIMESTAMP,
     UTCTIMEONLY,
+    TZTIMEONLY,
+    UTCDATEONLY,
+    UTCDATE,
+    LOCALMKTDATE,
+    LOCALMKTTIME,
     MONTHYEAR,
-    LOCALMKTDATE
+    TENOR,
+    PATTERN,
+    XID,
+    XIDREF,
+    RESERVED100PLUS,
+    RESERVED1000PLUS,
+    RESERVED4000PLUS,
+}
+
+const TIMESTAMP_FORMATS: [&str; 2] = ["%Y%m%d-%H:%M:%S%.f", "%Y%m%d-%H:%M:%S"];
+const TZTIMESTAMP_FORMATS: [&str; 3] = ["%Y%m%d-%H:%M:%S%.f%#z", "%Y%m%d-%H:%M:%S%#z", "%Y%m%d-%H:%M%#z"];
+const TIME_FORMATS: [&str; 3] = ["%H:%M:%S%.f", "%H:%M:%S", "%H:%M"];
+
+fn is_time(value: &str) -> bool {
+    TIME_FORMATS.iter().any(|format| chrono::NaiveTime::parse_from_str(value, format).is_ok())
+}
+
+/// Splits a TZTIMEONLY or TZTIMESTAMP value into the local part and its offset, "Z" or +/-HH[:MM].
+fn split_offset(value: &str) -> Option<(&str, &str)> {
+    if let Some(local) = value.strip_suffix('Z') {
+        return Some((local, "+00"));
+    }
+    let pos = value.rfind(['+', '-'])?;
+    if pos < value.len().saturating_sub(6) {
+        return None;
+    }
+    Some((&value[..pos], &value[pos..]))
+}
+
+/// Checks a float value: an optional '-' sign, then digits with at most one decimal point.
+fn is_decimal(value: &str) -> bool {
+    let digits = value.strip_prefix('-').unwrap_or(value);
+    let (integer, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+    !(integer.is_empty() && fraction.is_empty())
+        && integer.bytes().all(|b| b.is_ascii_digit())
+        && fraction.bytes().all(|b| b.is_ascii_digit())
+}
+
+fn is_code(value: &str, len: usize) -> bool {
+    value.len() == len && value.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+impl FieldType {
+    /// Maps a dictionary `type` attribute to its field type, unknown types map to STRING.
+    pub fn from_name(name: &str) -> FieldType {
+        match name {
+            "INT" => FieldType::INT,
+            "LENGTH" => FieldType::LENGTH,
+            "NUMINGROUP" => FieldType::NUMINGROUP,
+            "SEQNUM" => FieldType::SEQNUM,
+            "TAGNUM" => FieldType::TAGNUM,
+            "DAYOFMONTH" => FieldType::DAYOFMONTH,
+            "FLOAT" => FieldType::FLOAT,
+            "QTY" | "QUANTITY" => FieldType::QTY,
+            "PRICE" => FieldType::PRICE,
+            "PRICEOFFSET" => FieldType::PRICEOFFSET,
+            "AMT" => FieldType::AMT,
+            "PERCENTAGE" => FieldType::PERCENTAGE,
+            "CHAR" => FieldType::CHAR,
+            "BOOLEAN" => FieldType::BOOLEAN,
+            "MULTIPLECHARVALUE" => FieldType::MULTIPLECHARVALUE,
+            "MULTIPLEVALUESTRING" => FieldType::MULTIPLEVALUESTRING,
+            "MULTIPLESTRINGVALUE" => FieldType::MULTIPLESTRINGVALUE,
+            "CURRENCY" => FieldType::CURRENCY,
+            "EXCHANGE" => FieldType::EXCHANGE,
+            "COUNTRY" => FieldType::COUNTRY,
+            "LANGUAGE" => FieldType::LANGUAGE,
+            "DATA" => FieldType::DATA,
+            "XMLDATA" => FieldType::XMLDATA,
+            "UTCTIMESTAMP" => FieldType::UTCTIMESTAMP,
+            "MSTIMESTAMP" => FieldType::MSTIMESTAMP,
+            "TZTIMESTAMP" => FieldType::TZTIMESTAMP,
+            "UTCTIMEONLY" => FieldType::UTCTIMEONLY,
+            "TZTIMEONLY" => FieldType::TZTIMEONLY,
+            "UTCDATEONLY" => FieldType::UTCDATEONLY,
+            "UTCDATE" => FieldType::UTCDATE,
+            "LOCALMKTDATE" => FieldType::LOCALMKTDATE,
+            "LOCALMKTTIME" => FieldType::LOCALMKTTIME,
+            "MONTHYEAR" => FieldType::MONTHYEAR,
+            "TENOR" => FieldType::TENOR,
+            "PATTERN" => FieldType::PATTERN,
+            "XID" => FieldType::XID,
+            "XIDREF" => FieldType::XIDREF,
+            "RESERVED100PLUS" => FieldType::RESERVED100PLUS,
+            "RESERVED1000PLUS" => FieldType::RESERVED1000PLUS,
+            "RESERVED4000PLUS" => FieldType::RESERVED4000PLUS,
+            _ => FieldType::STRING, // default to STRING
+        }
+    }
+
+    /// Returns the FIX datatype name, as used by the dictionary `type` attribute.
+    pub fn name(&self) -> &'static str {
+        match self {
+            FieldType::INT => "INT",
+            FieldType::LENGTH => "LENGTH",
+            FieldType::NUMINGROUP => "NUMINGROUP",
+            FieldType::SEQNUM => "SEQNUM",
+            FieldType::TAGNUM => "TAGNUM",
+            FieldType::DAYOFMONTH => "DAYOFMONTH",
+            FieldType::FLOAT => "FLOAT",
+            FieldType::QTY => "QTY",
+            FieldType::PRICE => "PRICE",
+            FieldType::PRICEOFFSET => "PRICEOFFSET",
+            FieldType::AMT => "AMT",
+            FieldType::PERCENTAGE => "PERCENTAGE",
+            FieldType::CHAR => "CHAR",
+            FieldType::BOOLEAN => "BOOLEAN",
+            FieldType::STRING => "STRING",
+            FieldType::MULTIPLECHARVALUE => "MULTIPLECHARVALUE",
+            FieldType::MULTIPLEVALUESTRING => "MULTIPLEVALUESTRING",
+            FieldType::MULTIPLESTRINGVALUE => "MULTIPLESTRINGVALUE",
+            FieldType::CURRENCY => "CURRENCY",
+            FieldType::EXCHANGE => "EXCHANGE",
+            FieldType::COUNTRY => "COUNTRY",
+            FieldType::LANGUAGE => "LANGUAGE",
+            FieldType::DATA => "DATA",
+            FieldType::XMLDATA => "XMLDATA",
+            FieldType::UTCTIMESTAMP => "UTCTIMESTAMP",
+            FieldType::MSTIMESTAMP => "MSTIMESTAMP",
+            FieldType::TZTIMESTAMP => "TZTIMESTAMP",
+            FieldType::UTCTIMEONLY => "UTCTIMEONLY",
+            FieldType::TZTIMEONLY => "TZTIMEONLY",
+            FieldType::UTCDATEONLY => "UTCDATEONLY",
+            FieldType::UTCDATE => "UTCDATE",
+            FieldType::LOCALMKTDATE => "LOCALMKTDATE",
+            FieldType::LOCALMKTTIME => "LOCALMKTTIME",
+            FieldType::MONTHYEAR => "MONTHYEAR",
+            FieldType::TENOR => "TENOR",
+            FieldType::PATTERN => "PATTERN",
+            FieldType::XID => "XID",
+            FieldType::XIDREF => "XIDREF",
+            FieldType::RESERVED100PLUS => "RESERVED100PLUS",
+            FieldType::RESERVED1000PLUS => "RESERVED1000PLUS",
+            FieldType::RESERVED4000PLUS => "RESERVED4000PLUS",
+        }
+    }
+
+    /// Returns true for types held as integers.
+    pub fn is_integer(&self) -> bool {
+        matches!(self, FieldType::INT | FieldType::LENGTH | FieldType::NUMINGROUP | FieldType::SEQNUM | FieldType::TAGNUM
+            | FieldType::DAYOFMONTH | FieldType::RESERVED100PLUS | FieldType::RESERVED1000PLUS | FieldType::RESERVED4000PLUS)
+    }
+
+    /// Returns true for types held as floating-point numbers.
+    pub fn is_float(&self) -> bool {
+        matches!(self, FieldType::FLOAT | FieldType::QTY | FieldType::PRICE | FieldType::PRICEOFFSET | FieldType::AMT | FieldType::PERCENTAGE)
+    }
+
+    /// Returns true for types held as UTC date and times.
+    pub fn is_datetime(&self) -> bool {
+        matches!(self, FieldType::UTCTIMESTAMP | FieldType::MSTIMESTAMP | FieldType::TZTIMESTAMP
+            | FieldType::UTCDATEONLY | FieldType::UTCDATE | FieldType::LOCALMKTDATE)
+    }
+
+    /// Returns true for raw data types, whose size is given by a companion LENGTH field.
+    pub fn is_data(&self) -> bool {
+        matches!(self, FieldType::DATA | FieldType::XMLDATA)
+    }
+
+    /// Returns true if the text is a well formed value of the type.
+    pub fn is_valid(&self, value: &str) -> bool {
+        match self {
+            FieldType::LENGTH | FieldType::NUMINGROUP | FieldType::SEQNUM => value.parse::<i64>().map(|v| v >= 0).unwrap_or(false),
+            FieldType::TAGNUM => value.parse::<i64>().map(|v| v > 0).unwrap_or(false),
+            FieldType::DAYOFMONTH => value.parse::<u32>().map(|v| (1..=31).contains(&v)).unwrap_or(false),
+            t if t.is_integer() => value.parse::<i64>().is_ok(),
+            t if t.is_float() => is_decimal(value),
+            t if t.is_datetime() => self.parse_datetime(value).is_some(),
+            FieldType::CHAR => value.chars().count() == 1,
+            FieldType::BOOLEAN => value == "Y" || value == "N",
+            FieldType::MULTIPLECHARVALUE => value.split(' ').all(|v| v.chars().count() == 1),
+            FieldType::MULTIPLEVALUESTRING | FieldType::MULTIPLESTRINGVALUE => value.split(' ').all(|v| !v.is_empty()),
+            FieldType::CURRENCY => is_code(value, 3),
+            FieldType::COUNTRY | FieldType::LANGUAGE => is_code(value, 2),
+            FieldType::UTCTIMEONLY | FieldType::LOCALMKTTIME => is_time(value),
+            FieldType::TZTIMEONLY => split_offset(value).map(|(time, _)| is_time(time)).unwrap_or(false),
+            FieldType::MONTHYEAR => {
+                if !value.is_ascii() {
+                    return false;
+                }
+                let (month, rest) = value.split_at(value.len().min(6));
+                NaiveDate::parse_from_str(&format!("{}01", month), "%Y%m%d").is_ok()
+                    && (rest.is_empty()
+                        || NaiveDate::parse_from_str(value, "%Y%m%d").is_ok()
+                        || (rest.len() == 2 && rest.starts_with('w') && matches!(&rest[1..], "1" | "2" | "3" | "4" | "5")))
+            },
+            _ => true,
+        }
+    }
+
+    /// Parses a date and time value of the type, see `is_datetime`.
+    pub fn parse_datetime(&self, value: &str) -> Option<DateTime<Utc>> {
+        match self {
+            FieldType::UTCDATEONLY | FieldType::UTCDATE | FieldType::LOCALMKTDATE => {
+                NaiveDate::parse_from_str(value, "%Y%m%d").ok()
+                    .and_then(|date| date.and_hms_opt(0, 0, 0))
+                    .map(|dt| Utc.from_utc_datetime(&dt))
+            },
+            FieldType::TZTIMESTAMP => {
+                let (local, offset) = split_offset(value)?;
+                let value = format!("{}{}", local, offset);
+                TZTIMESTAMP_FORMATS.iter()
+                    .find_map(|format| DateTime::<FixedOffset>::parse_from_str(&value, format).ok())
+                    .map(|dt| dt.with_timezone(&Utc))
+            },
+            _ => {
+                TIMESTAMP_FORMATS.iter()
+                    .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+                    .map(|dt| Utc.from_utc_datetime(&dt))
+            },
+        }
+    }
+
+    /// Formats a date and time as a value of the type. Timestamps carry milliseconds, date only
+    /// types drop the time and time only types drop the date.
+    pub fn format_datetime(&self, value: &DateTime<Utc>) -> String {
+        let format = match self {
+            FieldType::UTCDATEONLY | FieldType::UTCDATE | FieldType::LOCALMKTDATE => "%Y%m%d",
+            FieldType::UTCTIMEONLY | FieldType::LOCALMKTTIME => "%H:%M:%S%.3f",
+            FieldType::TZTIMEONLY => "%H:%M:%S%.3fZ",
+            FieldType::TZTIMESTAMP => "%Y%m%d-%H:%M:%S%.3fZ",
+            FieldType::MONTHYEAR => "%Y%m",
+            _ => "%Y%m%d-%H:%M:%S%.3f",
+        };
+        value.format(format).to_string()
+    }
 }
 
 #[derive(Clone)]
@@ -37,20 +288,9 @@ pub struct Field {
     m_tag: i32,
     m_name: String,
     m_field_type: FieldType,
-}
-
-fn string_to_field_type(value: &str) -> FieldType {
-    match value {
-        "INT" | "LENGTH" | "DAYOFMONTH" => FieldType::INTEGER,
-        "QTY" | "FLOAT" | "PRICE" | "PRICEOFFSET" | "AMT" => FieldType::DOUBLE,
-        "UTCTIMESTAMP" => FieldType::UTCTIMESTAMP,
-        "MSTIMESTAMP" => FieldType::MSTIMESTAMP,
-        "UTCDATE" => FieldType::UTCDATE,
-        "UTCTIMEONLY" => FieldType::UTCTIMEONLY,
-        "MONTHYEAR" => FieldType::MONTHYEAR,
-        "LOCALMKTDATE" => FieldType::LOCALMKTDATE,
-        _ => FieldType::STRING // default to STRING
-    }
+    m_values: Vec<(String, String)>,
+    m_length_tag: Option<i32>,
+    m_data_tag: Option<i32>,
 }
 
 impl Field {
@@ -58,22 +298,74 @@ impl Field {
         Field { 
             m_tag: (tag),
             m_name: (name),
-            m_field_type: (string_to_field_type(ftype.as_str()))
+            m_field_type: (FieldType::from_name(ftype.as_str())),
+            m_values: Vec::new(),
+            m_length_tag: None,
+            m_data_tag: None,
         }
     }
 
     pub fn get_tag(&self) -> i32 {
         self.m_tag
     }
+
+    pub fn get_name(&self) -> &str {
+        &self.m_name
+    }
+
+    pub fn get_field_type(&self) -> &FieldType {
+        &self.m_field_type
+    }
+
+    /// Returns the tag of the LENGTH field giving the size of a DATA or XMLDATA field.
+    pub fn get_length_tag(&self) -> Option<i32> {
+        self.m_length_tag
+    }
+
+    /// Returns the tag of the DATA or XMLDATA field whose size a LENGTH field gives.
+    pub fn get_data_tag(&self) -> Option<i32> {
+        self.m_data_tag
+    }
+
+    pub fn set_length_tag(&mut self, tag: i32) {
+        self.m_length_tag = Some(tag);
+    }
+
+    pub fn set_data_tag(&mut self, tag: i32) {
+        self.m_data_tag = Some(tag);
+    }
+
+    /// Adds an allowed value, the `enum` attribute of a dictionary `<value>` element,
+    /// together with its description.
+    pub fn add_value(&mut self, value: String, description: String) {
+        self.m_values.push((value, description));
+    }
+
+    /// Returns true if the dictionary restricts the field to an enumerated set of values.
+    pub fn has_values(&self) -> bool {
+        !self.m_values.is_empty()
+    }
+
+    /// Returns true if the value is one of the enumerated values of the field. Fields
+    /// without enumerated values accept any value.
+    pub fn is_valid_value(&self, value: &str) -> bool {
+        !self.has_values() || self.m_values.iter().any(|(v, _)| v == value)
+    }
+
+    /// Returns the human readable description of an enumerated value, e.g. "BUY" for Side(54)=1.
+    pub fn get_value_description(&self, value: &str) -> Option<&str> {
+        self.m_values.iter().find(|(v, _)| v == value).map(|(_, d)| d.as_str())
+    }
+
+    /// Iterates the enumerated (value, description) pairs in declared order.
+    pub fn iter_values(&self) -> std::slice::Iter<'_, (String, String)> {
+        self.m_values.iter()
+    }
 }
 
 impl fmt::Display for FieldType {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match *self {
-            FieldType::INTEGER => write!(f, "INTEGER")?,
-            _ => write!(f, "STRING")?,
-        }
-        Ok(())
+        write!(f, "{}", self.name())
     }
 }
 
@@ -0,0 +1,157 @@
+use crate::ExtransError;
+
+use super::encoder::SOH;
+
+const BEGIN_STRING_PREFIX: &[u8] = b"8=FIX";
+const CHECK_SUM_PREFIX: &[u8] = b"10=";
+// "10=" followed by three digits and SOH
+const TRAILER_LENGTH: usize = 7;
+// longest BeginString field we wait for before giving up on a frame start
+const MAX_BEGIN_STRING_LENGTH: usize = 32;
+// longest BodyLength field we wait for, "9=" followed by up to 9 digits and SOH
+const MAX_BODY_LENGTH_FIELD: usize = 12;
+
+/// Splits a TCP byte stream into complete FIX messages.
+///
+/// Chunks of any size are added with `push` and complete frames taken with `next_frame`. A frame
+/// starts with BeginString(8), its size is given by BodyLength(9) and it ends with the CheckSum(10)
+/// trailer. Bytes ahead of a frame start are discarded. A frame whose BodyLength is malformed or
+/// does not lead to a CheckSum trailer is reported as an `ExtransError::FrameError`, after which
+/// the framer resynchronises on the next BeginString, so `next_frame` can be called again.
+///
+/// The CheckSum value itself is not verified, that is left to the decoder.
+pub struct FixFramer {
+    m_buffer: Vec<u8>,
+    m_max_body_length: usize,
+}
+
+enum Frame {
+    Complete(usize),
+    Incomplete,
+    Malformed(String),
+}
+
+impl Default for FixFramer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FixFramer {
+    pub fn new() -> Self {
+        FixFramer {
+            m_buffer: Vec::with_capacity(4096),
+            m_max_body_length: 1024 * 1024,
+        }
+    }
+
+    /// Sets the largest BodyLength accepted, larger frames are treated as corrupt.
+    pub fn with_max_body_length(mut self, max_body_length: usize) -> Self {
+        self.m_max_body_length = max_body_length;
+        self
+    }
+
+    /// Adds bytes received from the stream.
+    pub fn push(&mut self, data: &[u8]) {
+        self.m_buffer.extend_from_slice(data);
+    }
+
+    /// Returns the number of buffered bytes not yet returned as a frame.
+    pub fn buffered(&self) -> usize {
+        self.m_buffer.len()
+    }
+
+    /// Discards all buffered bytes, e.g. after the connection is reset.
+    pub fn clear(&mut self) {
+        self.m_buffer.clear();
+    }
+
+    /// Returns the next complete frame, or None if more bytes are needed.
+    pub fn next_frame(&mut self) -> Result<Option<Vec<u8>>, ExtransError> {
+        let start = match self.find_begin_string() {
+            Some(start) => start,
+            None => {
+                // keep a possible partial "8=FIX" at the end of the buffer
+                let keep = self.m_buffer.len().min(BEGIN_STRING_PREFIX.len() - 1);
+                let discard = self.m_buffer.len() - keep;
+                if discard > 0 {
+                    log::debug!("discarding {} bytes received outside of a FIX frame", discard);
+                    self.m_buffer.drain(..discard);
+                }
+                return Ok(None);
+            }
+        };
+
+        if start > 0 {
+            log::debug!("discarding {} bytes received ahead of a FIX frame", start);
+            self.m_buffer.drain(..start);
+        }
+
+        match self.frame_length() {
+            Frame::Complete(length) => Ok(Some(self.m_buffer.drain(..length).collect())),
+            Frame::Incomplete => Ok(None),
+            Frame::Malformed(reason) => {
+                // skip this frame start so that the next call resynchronises on the following one
+                self.m_buffer.drain(..1);
+                Err(ExtransError::FrameError(reason))
+            },
+        }
+    }
+
+    /// Finds a BeginString field, ignoring "8=FIX" where it is the tail of another tag, e.g. 58=FIX.
+    fn find_begin_string(&self) -> Option<usize> {
+        self.m_buffer.windows(BEGIN_STRING_PREFIX.len())
+            .enumerate()
+            .find(|(pos, window)| *window == BEGIN_STRING_PREFIX && (*pos == 0 || !self.m_buffer[pos - 1].is_ascii_digit()))
+            .map(|(pos, _)| pos)
+    }
+
+    /// Works out the length of the frame at the start of the buffer.
+    fn frame_length(&self) -> Frame {
+        let buffer = &self.m_buffer;
+
+        let body_length_start = match buffer.iter().position(|b| *b == SOH) {
+            Some(end) if end <= MAX_BEGIN_STRING_LENGTH => end + 1,
+            Some(_) => return Frame::Malformed("BeginString(8) is too long".to_string()),
+            None if buffer.len() > MAX_BEGIN_STRING_LENGTH => return Frame::Malformed("BeginString(8) is not SOH terminated".to_string()),
+            None => return Frame::Incomplete,
+        };
+
+        let field = &buffer[body_length_start..];
+        let available = field.len().min(MAX_BODY_LENGTH_FIELD);
+        if available < 2 {
+            return Frame::Incomplete;
+        }
+        if &field[..2] != b"9=" {
+            return Frame::Malformed("BeginString(8) is not followed by BodyLength(9)".to_string());
+        }
+
+        let body_start = match field[..available].iter().position(|b| *b == SOH) {
+            Some(end) => body_length_start + end + 1,
+            None if available == MAX_BODY_LENGTH_FIELD => return Frame::Malformed("BodyLength(9) is too long".to_string()),
+            None => return Frame::Incomplete,
+        };
+
+        let value = &buffer[body_length_start + 2..body_start - 1];
+        let body_length = match std::str::from_utf8(value).ok().filter(|v| v.bytes().all(|b| b.is_ascii_digit())).and_then(|v| v.parse::<usize>().ok()) {
+            Some(body_length) => body_length,
+            None => return Frame::Malformed(format!("invalid BodyLength(9) '{}'", String::from_utf8_lossy(value))),
+        };
+        if body_length > self.m_max_body_length {
+            return Frame::Malformed(format!("BodyLength(9) {} exceeds the maximum of {}", body_length, self.m_max_body_length));
+        }
+
+        let trailer_start = body_start + body_length;
+        let frame_length = trailer_start + TRAILER_LENGTH;
+        if buffer.len() < frame_length {
+            return Frame::Incomplete;
+        }
+
+        let trailer = &buffer[trailer_start..frame_length];
+        if !trailer.starts_with(CHECK_SUM_PREFIX) || !trailer[3..6].iter().all(|b| b.is_ascii_digit()) || trailer[6] != SOH {
+            return Frame::Malformed(format!("BodyLength(9) {} does not lead to a CheckSum(10) trailer", body_length));
+        }
+
+        Frame::Complete(frame_length)
+    }
+}
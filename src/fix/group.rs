@@ -1,11 +1,15 @@
+use std::collections::{HashMap, HashSet};
 use std::fmt;
 use super::field::Field;
 
+#[derive(Clone)]
 pub struct Group {
     m_parent_tag: i32,
     m_first_tag: i32,
     m_tags: Vec<i32>,
     m_fields: Vec<Field>,
+    m_required: HashSet<i32>,
+    m_groups: HashMap<i32, Group>,
 }
 
 impl Group {
@@ -14,7 +18,9 @@ impl Group {
             m_parent_tag: (parent),
             m_first_tag: (0),
             m_tags: Vec::new(),
-            m_fields: Vec::new()
+            m_fields: Vec::new(),
+            m_required: HashSet::new(),
+            m_groups: HashMap::new(),
         }
     }
 
@@ -22,23 +28,54 @@ impl Group {
         self.m_parent_tag
     }
 
-    pub fn add_field(&mut self, field: Field) {
+    /// Returns the delimiter field tag, the first field of every group entry.
+    pub fn get_first_tag(&self) -> i32 {
+        self.m_first_tag
+    }
+
+    pub fn add_field(&mut self, field: Field, required: bool) {
         if self.m_first_tag == 0 {
             self.m_first_tag = field.get_tag();
         }
 
+        if required {
+            self.m_required.insert(field.get_tag());
+        }
+
         self.m_tags.push(field.get_tag());
         self.m_fields.push(field);
     }
 
+    /// Adds a nested repeating group, `field` being the nested group count (NUMINGROUP) field.
+    pub fn add_group(&mut self, field: Field, group: Group, required: bool) {
+        self.add_field(field, required);
+        self.m_groups.insert(group.get_parent_tag(), group);
+    }
+
+    /// Looks up a nested repeating group by its count tag.
+    pub fn get_group(&self, tag: i32) -> Option<&Group> {
+        self.m_groups.get(&tag)
+    }
+
+    pub fn iter_groups(&self) -> impl Iterator<Item = &Group> {
+        self.m_groups.values()
+    }
+
     pub fn is_first_tag(&self, tag: i32) -> bool {
         tag == self.m_first_tag
     }
 
+    /// Returns true if the tag is a field of the group entries, nested group count fields
+    /// included. Fields of nested group entries are not considered.
     pub fn has_tag(&self, tag: i32) -> bool {
         self.m_tags.contains(&tag)
     }
 
+    /// Returns true if the field is mandatory within each group entry.
+    pub fn is_required(&self, tag: i32) -> bool {
+        self.m_required.contains(&tag)
+    }
+
     pub fn iter_fields(&self) -> std::slice::Iter<'_, Field> {
         self.m_fields.iter()
     }
@@ -48,8 +85,11 @@ impl fmt::Display for Group {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         writeln!(f, "group parent tag: {}", self.m_parent_tag)?;
         for field in self.iter_fields() {
-            writeln!(f, "    {}", field)?;
+            match self.m_groups.get(&field.get_tag()) {
+                Some(group) => writeln!(f, "    {}", group)?,
+                None => writeln!(f, "    {}", field)?,
+            }
         }
         Ok(())
     }
-}
\ No newline at end of file
+}
@@ -1,15 +1,18 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fmt;
 
 use super::field::Field;
 use super::group::Group;
 
+#[derive(Clone)]
 pub struct Message {
     m_name: String,
     m_type: String,
     m_cat: String,
-    m_fields: HashMap<i32, Field>,
+    m_fields: Vec<Field>,
+    m_positions: HashMap<i32, usize>,
     m_groups: HashMap<i32, Group>,
+    m_required: HashSet<i32>,
 }
 
 impl Message {
@@ -18,16 +21,74 @@ impl Message {
             m_name: name,
             m_type: msgtype,
             m_cat:msgcat,
-            m_fields: HashMap::new(),
+            m_fields: Vec::new(),
+            m_positions: HashMap::new(),
             m_groups: HashMap::new(),
+            m_required: HashSet::new(),
         }
     }
 
-    pub fn add_field(&mut self, field: Field) {
-        self.m_fields.insert(field.get_tag(), field);
+    pub fn get_name(&self) -> &str {
+        &self.m_name
     }
 
-    pub fn add_group(&mut self, group: Group) {
+    pub fn get_type(&self) -> &str {
+        &self.m_type
+    }
+
+    pub fn get_category(&self) -> &str {
+        &self.m_cat
+    }
+
+    pub fn get_field(&self, tag: i32) -> Option<&Field> {
+        self.m_positions.get(&tag).map(|pos| &self.m_fields[*pos])
+    }
+
+    pub fn get_group(&self, tag: i32) -> Option<&Group> {
+        self.m_groups.get(&tag)
+    }
+
+    /// Returns true if the field or group count field is defined for the message.
+    pub fn has_tag(&self, tag: i32) -> bool {
+        self.m_positions.contains_key(&tag)
+    }
+
+    /// Returns true if the field or group count field is mandatory for the message.
+    pub fn is_required(&self, tag: i32) -> bool {
+        self.m_required.contains(&tag)
+    }
+
+    /// Iterates the fields of the message in declared order, group count fields included.
+    pub fn iter_fields(&self) -> std::slice::Iter<'_, Field> {
+        self.m_fields.iter()
+    }
+
+    pub fn iter_groups(&self) -> impl Iterator<Item = &Group> {
+        self.m_groups.values()
+    }
+
+    /// Returns the tags of all fields and group count fields defined for the message,
+    /// in declared order.
+    pub fn get_tags(&self) -> Vec<i32> {
+        self.m_fields.iter().map(|f| f.get_tag()).collect()
+    }
+
+    /// Adds a field to the message. A field that is already defined keeps its original
+    /// position, as happens when a tag is reachable through more than one component.
+    pub fn add_field(&mut self, field: Field, required: bool) {
+        if required {
+            self.m_required.insert(field.get_tag());
+        }
+
+        if !self.m_positions.contains_key(&field.get_tag()) {
+            self.m_positions.insert(field.get_tag(), self.m_fields.len());
+            self.m_fields.push(field);
+        }
+    }
+
+    /// Adds a repeating group, `field` being the group count (NUMINGROUP) field.
+    pub fn add_group(&mut self, field: Field, group: Group, required: bool) {
+        self.add_field(field, required);
         self.m_groups.insert(group.get_parent_tag(), group);
     }
 }
@@ -35,14 +96,12 @@ impl Message {
 impl fmt::Display for Message {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         writeln!(f, "{} {} {}", self.m_name, self.m_type, self.m_cat)?;
-        for (_t, n)in self.m_fields.iter() {
-            writeln!(f, "    {}", n)?;
-        } 
-
-        for (_t, g) in self.m_groups.iter() {
-            writeln!(f, "    {}", g)?;
-            writeln!(f, "")?;
+        for field in self.m_fields.iter() {
+            match self.m_groups.get(&field.get_tag()) {
+                Some(group) => writeln!(f, "    {}", group)?,
+                None => writeln!(f, "    {}", field)?,
+            }
         }
         Ok(())
     }
-}
\ No newline at end of file
+}
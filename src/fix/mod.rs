@@ -3,18 +3,73 @@ mod group;
 mod component;
 mod message;
 mod dictionary;
+mod dictionary_issue;
+mod cache;
+mod orchestra;
+mod encoder;
+mod decoder;
+mod view;
+mod framer;
+mod strictness;
+mod validator;
+mod violation;
+pub mod session;
 
-
-use dictionary::Dictionary;
+pub use dictionary::Dictionary;
+pub use dictionary_issue::DictionaryIssue;
+pub use field::{Field, FieldType};
+pub use group::Group;
+pub use message::Message;
+pub use view::FixMessageView;
+pub use framer::FixFramer;
+pub use strictness::Strictness;
+pub use violation::Violation;
 use crate::{Extrans, ExtransError};
-//use crate::properties::Properties;
+use std::collections::HashMap;
+
+use crate::ido::{Ido, IdoKeyT};
+use crate::properties::Properties;
+/// FIX tag=value codec.
+///
+/// Several dictionaries may be loaded at once, one per FIX version, keyed by their BeginString(8).
+/// Messages are dispatched to the dictionary of the BeginString they carry, or when encoding the
+/// BeginString held in the Ido, falling back to the first dictionary loaded. A FIXT transport
+/// dictionary is combined with every application dictionary loaded, see `Dictionary::combine`,
+/// and FIXT messages are dispatched on their ApplVerID(1128), falling back to the default
+/// application version.
 pub struct FIX {
     //m_properties: Properties,
+    m_dictionaries: HashMap<String, Dictionary>,
+    m_combined: HashMap<(String, String), Dictionary>,
+    m_default_begin_string: Option<String>,
+    m_default_appl_ver_id: Option<String>,
+    m_strictness: Strictness,
 }
 
+const APPL_VER_ID: IdoKeyT = 1128;
+
 impl Extrans for FIX {
-    fn encode(&self) -> String {
-        "Hello".to_string()
+    /// Encodes the Ido as a FIX tag=value message of the given message type. The header,
+    /// body and trailer fields are ordered according to the dictionary of the BeginString(8)
+    /// and ApplVerID(1128) held in the Ido, or the default dictionary.
+    fn encode(&self, ido: &Ido, msgtype: &str) -> Result<Vec<u8>, ExtransError> {
+        let dictionary = self.resolve_ido(ido).map_err(ExtransError::EncodeError)?;
+        encoder::encode(dictionary, ido, msgtype)
+    }
+
+    /// Decodes a raw FIX tag=value message into an Ido keyed by tag number. Values are typed
+    /// according to the dictionary of the message BeginString(8) and ApplVerID(1128), and
+    /// repeating groups become Ido arrays. BodyLength(9) and CheckSum(10) are verified according
+    /// to the configured `Strictness`.
+    fn decode(&self, data: &[u8]) -> Result<Ido, ExtransError> {
+        let dictionary = self.resolve_data(data).map_err(ExtransError::DecodeError)?;
+        decoder::decode(dictionary, data, self.m_strictness)
+    }
+}
+
+impl Default for FIX {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -22,17 +77,157 @@ impl FIX {
     pub fn new() -> Self {
         FIX {
             //m_properties: Properties;
+            m_dictionaries: HashMap::new(),
+            m_combined: HashMap::new(),
+            m_default_begin_string: None,
+            m_default_appl_ver_id: None,
+            m_strictness: Strictness::STRICT,
         }
     }
 
-    pub fn init() {
+    /// Configures the codec from properties:
+    ///
+    /// - `fix_strictness`: strict, warn or ignore, how BodyLength(9) and CheckSum(10) mismatches
+    ///   are handled when decoding. Defaults to strict.
+    pub fn init(&mut self, properties: &Properties) -> Result<(), ExtransError> {
+        let strictness = properties.get_default::<String>("fix_strictness", Strictness::STRICT.to_string());
+        self.m_strictness = strictness.parse::<Strictness>().map_err(ExtransError::ParseError)?;
+        Ok(())
+    }
+
+    pub fn get_strictness(&self) -> Strictness {
+        self.m_strictness
+    }
 
+    pub fn set_strictness(&mut self, strictness: Strictness) {
+        self.m_strictness = strictness;
     }
 
+    /// Returns the default dictionary, allowing tooling to query the field, message and
+    /// group layouts it defines.
+    pub fn get_dictionary(&self) -> Option<&Dictionary> {
+        self.resolve(None, None).ok()
+    }
+
+    /// Returns the dictionary messages with the given BeginString(8) and ApplVerID(1128) are
+    /// dispatched to.
+    pub fn get_dictionary_for(&self, begin_string: &str, appl_ver_id: Option<&str>) -> Option<&Dictionary> {
+        self.resolve(Some(begin_string), appl_ver_id).ok()
+    }
+
+    /// Sets the BeginString used for Idos that do not hold one, by default that of the first
+    /// dictionary loaded.
+    pub fn set_default_begin_string(&mut self, begin_string: &str) {
+        self.m_default_begin_string = Some(begin_string.to_string());
+    }
+
+    /// Sets the ApplVerID used for FIXT messages that do not carry one, typically the
+    /// DefaultApplVerID(1137) agreed at Logon. By default that of the first FIX 5.0 or later
+    /// dictionary loaded.
+    pub fn set_default_appl_ver_id(&mut self, appl_ver_id: &str) {
+        self.m_default_appl_ver_id = Some(appl_ver_id.to_string());
+    }
+
+    fn resolve(&self, begin_string: Option<&str>, appl_ver_id: Option<&str>) -> Result<&Dictionary, String> {
+        let begin_string = match begin_string.or(self.m_default_begin_string.as_deref()) {
+            Some(begin_string) => begin_string,
+            None => return Err("no FIX dictionary loaded".to_string()),
+        };
+
+        let dictionary = match self.m_dictionaries.get(begin_string) {
+            Some(dictionary) => dictionary,
+            None => return Err(format!("no FIX dictionary loaded for BeginString '{}'", begin_string)),
+        };
+        if !dictionary.is_transport() {
+            return Ok(dictionary);
+        }
+
+        match appl_ver_id.or(self.m_default_appl_ver_id.as_deref()) {
+            Some(appl_ver_id) => self.m_combined.get(&(begin_string.to_string(), appl_ver_id.to_string()))
+                .ok_or_else(|| format!("no FIX application dictionary loaded for ApplVerID '{}'", appl_ver_id)),
+            None => Ok(dictionary),
+        }
+    }
+
+    fn resolve_ido(&self, ido: &Ido) -> Result<&Dictionary, String> {
+        let begin_string = ido.get_string(&encoder::BEGIN_STRING);
+        let appl_ver_id = ido.get_string(&APPL_VER_ID);
+        self.resolve(begin_string.as_deref(), appl_ver_id.as_deref())
+    }
+
+    fn resolve_data(&self, data: &[u8]) -> Result<&Dictionary, String> {
+        self.resolve(decoder::peek_field(data, encoder::BEGIN_STRING), decoder::peek_field(data, APPL_VER_ID))
+    }
+
+    /// Creates a borrowed view over a raw FIX message, for reading a few tags without
+    /// decoding the whole message into an Ido. The message is verified as `decode` would.
+    pub fn view<'a>(&'a self, data: &'a [u8]) -> Result<FixMessageView<'a>, ExtransError> {
+        let dictionary = self.resolve_data(data).map_err(ExtransError::DecodeError)?;
+        decoder::verify(data, self.m_strictness)?;
+        FixMessageView::new(dictionary, data)
+    }
+
+    /// Checks an Ido against the dictionary definition of the message type and returns
+    /// every violation found: missing required fields, undefined tags, values outside the field
+    /// enumeration or of the wrong datatype, group counts that do not match the entries present
+    /// and group entries without their delimiter field. An empty list means the message is valid.
+    pub fn validate(&self, ido: &Ido, msgtype: &str) -> Result<Vec<Violation>, ExtransError> {
+        let dictionary = self.resolve_ido(ido).map_err(ExtransError::SetupError)?;
+        Ok(validator::validate(dictionary, ido, msgtype))
+    }
+
+    /// Loads a dictionary and registers it under its BeginString, replacing any dictionary
+    /// already loaded for that FIX version.
     pub fn load_dictionary(&mut self, filename: &str) -> Result<(), ExtransError> {
         let mut fix_dict: Dictionary = Dictionary::new();
         fix_dict.load(filename)?;
+        self.register(fix_dict);
+        Ok(())
+    }
 
+    /// Loads a dictionary through its binary cache, see `Dictionary::load_cached`, and registers
+    /// it as `load_dictionary` does.
+    pub fn load_dictionary_cached(&mut self, filename: &str, cache_filename: &str) -> Result<(), ExtransError> {
+        let mut fix_dict: Dictionary = Dictionary::new();
+        fix_dict.load_cached(filename, cache_filename)?;
+        self.register(fix_dict);
         Ok(())
     }
-}
\ No newline at end of file
+
+    /// Applies a dictionary overlay, e.g. venue specific tags, to the dictionary loaded for
+    /// `begin_string` with `Dictionary::load_overlay`. Returns the issues the overlay raised.
+    pub fn load_overlay(&mut self, begin_string: &str, filename: &str) -> Result<Vec<DictionaryIssue>, ExtransError> {
+        let mut fix_dict = match self.m_dictionaries.get(begin_string) {
+            Some(fix_dict) => fix_dict.clone(),
+            None => return Err(ExtransError::SetupError(format!("no FIX dictionary loaded for {}", begin_string))),
+        };
+        let issues = fix_dict.load_overlay(filename)?;
+        self.register(fix_dict);
+        Ok(issues)
+    }
+
+    /// Registers a dictionary under its BeginString and builds the combined dictionaries it
+    /// takes part in.
+    fn register(&mut self, fix_dict: Dictionary) {
+        let begin_string = fix_dict.get_begin_string().to_string();
+        if fix_dict.is_transport() {
+            for application in self.m_dictionaries.values().filter(|d| !d.is_transport()) {
+                let appl_ver_id = application.get_appl_ver_id().unwrap_or_default().to_string();
+                self.m_combined.insert((begin_string.clone(), appl_ver_id), Dictionary::combine(&fix_dict, application));
+            }
+        } else if let Some(appl_ver_id) = fix_dict.get_appl_ver_id() {
+            for transport in self.m_dictionaries.values().filter(|d| d.is_transport()) {
+                let key = (transport.get_begin_string().to_string(), appl_ver_id.to_string());
+                self.m_combined.insert(key, Dictionary::combine(transport, &fix_dict));
+            }
+            if self.m_default_appl_ver_id.is_none() && begin_string.starts_with("FIX.5") {
+                self.m_default_appl_ver_id = Some(appl_ver_id.to_string());
+            }
+        }
+
+        if self.m_default_begin_string.is_none() {
+            self.m_default_begin_string = Some(begin_string.clone());
+        }
+        self.m_dictionaries.insert(begin_string, fix_dict);
+    }
+}
@@ -0,0 +1,298 @@
+use std::collections::HashMap;
+
+use crate::ExtransError;
+
+use super::component::{Component, ComponentFieldType};
+use super::dictionary_issue::DictionaryIssue;
+use super::field::Field;
+use super::message::Message;
+
+const STANDARD_HEADER: &str = "StandardHeader";
+const STANDARD_TRAILER: &str = "StandardTrailer";
+
+/// The type, name and required flag of each member of a definition, in declared order.
+type Entries = Vec<(ComponentFieldType, String, bool)>;
+
+/// The underlying datatype of a codeSet and its (value, description) codes.
+type CodeSet<'a> = (&'a str, Vec<(String, String)>);
+
+/// The definitions read from a FIX Orchestra repository, in the form the QuickFIX dictionary
+/// loader builds them: fields with their raw dictionary type, component, message, header and
+/// trailer definitions referring to fields and components by name, and groups named after their
+/// NumInGroup field.
+pub(crate) struct Repository {
+    pub(crate) m_begin_string: String,
+    pub(crate) m_fields: Vec<(Field, String)>,
+    pub(crate) m_components: Vec<Component>,
+    pub(crate) m_messages: Vec<(Message, Component)>,
+    pub(crate) m_header: Component,
+    pub(crate) m_trailer: Component,
+    pub(crate) m_issues: Vec<DictionaryIssue>,
+}
+
+/// Converts an Orchestra datatype name, e.g. "UTCTimestamp" or "NumInGroup", to the QuickFIX
+/// type name.
+fn datatype_name(datatype: &str) -> String {
+    datatype.to_uppercase()
+}
+
+/// Converts an Orchestra code name to a QuickFIX value description, e.g. "SellShort" to
+/// "SELL_SHORT", "ClientID" to "CLIENT_ID" or "ISINNumber" to "ISIN_NUMBER".
+fn code_description(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut description = String::with_capacity(name.len() + 4);
+    for (i, c) in chars.iter().enumerate() {
+        if i > 0 && c.is_ascii_uppercase() {
+            let previous = chars[i - 1];
+            let next_lower = chars.get(i + 1).map(|n| n.is_ascii_lowercase()).unwrap_or(false);
+            if previous.is_ascii_lowercase() || previous.is_ascii_digit() || (previous.is_ascii_uppercase() && next_lower) {
+                description.push('_');
+            }
+        }
+        description.push(c.to_ascii_uppercase());
+    }
+    description
+}
+
+/// Required and constant fields are mandatory, forbidden and ignored fields are left out.
+fn presence(node: &roxmltree::Node) -> Option<bool> {
+    match node.attribute("presence").unwrap_or("optional") {
+        "required" | "constant" => Some(true),
+        "forbidden" | "ignored" => None,
+        _ => Some(false),
+    }
+}
+
+fn missing_attribute(context: &str, element: &str, attribute: &str) -> DictionaryIssue {
+    DictionaryIssue::MissingAttribute { context: context.to_string(), element: element.to_string(), attribute: attribute.to_string() }
+}
+
+/// Iterates the `element` children of a top level section, e.g. the fields of `fields`.
+fn children<'a, 'input>(root: &roxmltree::Node<'a, 'input>, section: &'a str, element: &'a str) -> impl Iterator<Item = roxmltree::Node<'a, 'input>> {
+    root.children()
+        .filter(move |n| n.has_tag_name(section))
+        .flat_map(|n| n.children())
+        .filter(move |n| n.has_tag_name(element))
+}
+
+struct Reader<'a, 'input> {
+    m_field_names: HashMap<&'a str, &'a str>,
+    m_components: HashMap<&'a str, &'a str>,
+    m_groups: HashMap<&'a str, roxmltree::Node<'a, 'input>>,
+    m_issues: Vec<DictionaryIssue>,
+}
+
+impl<'a, 'input> Reader<'a, 'input> {
+    /// Reads the fieldRef, componentRef and groupRef children of a component, group or message
+    /// structure. Referenced groups are registered with `component` under the name of their
+    /// NumInGroup field, nested groups included. `path` holds the ids of the groups being read.
+    fn read_entries(&mut self, component: &mut Component, node: &roxmltree::Node<'a, 'input>, context: &str, path: &mut Vec<&'a str>) -> Result<Entries, ExtransError> {
+        let mut entries = Vec::new();
+        for child in node.children().filter(|n| n.is_element()) {
+            let element = child.tag_name().name();
+            if !matches!(element, "fieldRef" | "componentRef" | "groupRef") {
+                continue;
+            }
+            let id = match child.attribute("id") {
+                Some(id) => id,
+                None => {
+                    self.m_issues.push(missing_attribute(context, element, "id"));
+                    continue;
+                }
+            };
+            let required = match presence(&child) {
+                Some(required) => required,
+                None => continue,
+            };
+
+            match element {
+                "fieldRef" => match self.m_field_names.get(id) {
+                    Some(name) => entries.push((ComponentFieldType::FIELD, name.to_string(), required)),
+                    None => self.m_issues.push(DictionaryIssue::UnknownField { context: context.to_string(), name: id.to_string() }),
+                },
+                "componentRef" => match self.m_components.get(id) {
+                    Some(name) if *name == STANDARD_HEADER || *name == STANDARD_TRAILER => {},
+                    Some(name) => entries.push((ComponentFieldType::COMPONENT, name.to_string(), required)),
+                    None => self.m_issues.push(DictionaryIssue::UndefinedComponent { context: context.to_string(), name: id.to_string() }),
+                },
+                _ => {
+                    if let Some((name, group_fields)) = self.read_group(component, id, context, path)? {
+                        component.add_nested_group(name.clone(), group_fields);
+                        entries.push((ComponentFieldType::GROUP, name, required));
+                    }
+                },
+            }
+        }
+        Ok(entries)
+    }
+
+    /// Reads the group with the given id, returning the name of its NumInGroup field and its
+    /// entries, or None if the group cannot be used.
+    fn read_group(&mut self, component: &mut Component, id: &'a str, context: &str, path: &mut Vec<&'a str>) -> Result<Option<(String, Entries)>, ExtransError> {
+        let group = match self.m_groups.get(id) {
+            Some(group) => *group,
+            None => {
+                self.m_issues.push(DictionaryIssue::UndefinedComponent { context: context.to_string(), name: id.to_string() });
+                return Ok(None);
+            }
+        };
+        if path.contains(&id) {
+            return Err(ExtransError::SetupError(format!("cyclic group reference: {} -> {}", path.join(" -> "), id)));
+        }
+
+        let group_context = format!("{} group {}", context, group.attribute("name").unwrap_or(id));
+        let count_id = group.children().find(|n| n.has_tag_name("numInGroup")).and_then(|n| n.attribute("id"));
+        let name = match count_id.map(|count_id| (count_id, self.m_field_names.get(count_id))) {
+            Some((_, Some(name))) => name.to_string(),
+            Some((count_id, None)) => {
+                self.m_issues.push(DictionaryIssue::UnknownField { context: group_context, name: count_id.to_string() });
+                return Ok(None);
+            },
+            None => {
+                self.m_issues.push(missing_attribute(&group_context, "group", "numInGroup"));
+                return Ok(None);
+            },
+        };
+
+        path.push(id);
+        let group_fields = self.read_entries(component, &group, &group_context, path)?;
+        path.pop();
+        Ok(Some((name, group_fields)))
+    }
+
+    fn read_definition(&mut self, name: &str, node: &roxmltree::Node<'a, 'input>, context: &str) -> Result<Component, ExtransError> {
+        let mut component = Component::new(name.to_string());
+        let entries = self.read_entries(&mut component, node, context, &mut Vec::new())?;
+        component.add_entries(entries);
+        Ok(component)
+    }
+}
+
+/// Reads an Orchestra repository, the root element of a repository.xml file. Elements in the
+/// datatypes, metadata, sections and other sections are not needed and are ignored. Of several
+/// message scenarios only the base scenario is read, and a field refers to a codeSet through
+/// its type, the codes becoming the field values. Session category messages are admin messages.
+pub(crate) fn read_repository(root: &roxmltree::Node) -> Result<Repository, ExtransError> {
+    let mut begin_string = root.attribute("version").or_else(|| root.attribute("name")).unwrap_or("FIX.Latest").to_string();
+    if let Some(pos) = begin_string.find('_') {
+        begin_string.truncate(pos);
+    }
+
+    let mut reader = Reader {
+        m_field_names: HashMap::new(),
+        m_components: HashMap::new(),
+        m_groups: HashMap::new(),
+        m_issues: Vec::new(),
+    };
+
+    let mut code_sets: HashMap<&str, CodeSet> = HashMap::new();
+    for code_set in children(root, "codeSets", "codeSet") {
+        let (name, datatype) = match (code_set.attribute("name"), code_set.attribute("type")) {
+            (Some(name), Some(datatype)) => (name, datatype),
+            (name, _) => {
+                let context = format!("codeSet {}", name.unwrap_or("?"));
+                reader.m_issues.push(missing_attribute(&context, "codeSet", if name.is_none() { "name" } else { "type" }));
+                continue;
+            }
+        };
+        let codes = code_set.children()
+            .filter(|n| n.has_tag_name("code"))
+            .filter_map(|code| Some((code.attribute("value")?.to_string(), code_description(code.attribute("name").unwrap_or("")))))
+            .collect();
+        code_sets.insert(name, (datatype, codes));
+    }
+
+    let mut fields = Vec::new();
+    for field in children(root, "fields", "field") {
+        let context = format!("field {}", field.attribute("name").or(field.attribute("id")).unwrap_or("?"));
+        let (id, name, datatype) = match (field.attribute("id"), field.attribute("name"), field.attribute("type")) {
+            (Some(id), Some(name), Some(datatype)) => (id, name, datatype),
+            (id, name, _) => {
+                let attribute = if id.is_none() { "id" } else if name.is_none() { "name" } else { "type" };
+                reader.m_issues.push(missing_attribute(&context, "field", attribute));
+                continue;
+            }
+        };
+        let tag = match id.parse::<i32>() {
+            Ok(tag) => tag,
+            Err(_) => {
+                reader.m_issues.push(DictionaryIssue::InvalidAttribute { context, attribute: "id".to_string(), value: id.to_string() });
+                continue;
+            }
+        };
+
+        let (field_type, codes) = match code_sets.get(datatype) {
+            Some((code_set_type, codes)) => (datatype_name(code_set_type), codes.as_slice()),
+            None => (datatype_name(datatype), [].as_slice()),
+        };
+        let mut f = Field::new(tag, name.to_string(), field_type.clone());
+        for (value, description) in codes {
+            f.add_value(value.clone(), description.clone());
+        }
+        fields.push((f, field_type));
+        reader.m_field_names.insert(id, name);
+    }
+
+    let mut component_nodes = Vec::new();
+    for component in children(root, "components", "component") {
+        match (component.attribute("id"), component.attribute("name")) {
+            (Some(id), Some(name)) => {
+                reader.m_components.insert(id, name);
+                component_nodes.push((name, component));
+            },
+            (id, _) => {
+                let context = format!("component {}", component.attribute("name").unwrap_or("?"));
+                reader.m_issues.push(missing_attribute(&context, "component", if id.is_none() { "id" } else { "name" }));
+            },
+        }
+    }
+    for group in children(root, "groups", "group") {
+        match group.attribute("id") {
+            Some(id) => { reader.m_groups.insert(id, group); },
+            None => reader.m_issues.push(missing_attribute(&format!("group {}", group.attribute("name").unwrap_or("?")), "group", "id")),
+        }
+    }
+
+    let mut components = Vec::new();
+    let mut header = Component::new("Header".to_string());
+    let mut trailer = Component::new("Trailer".to_string());
+    for (name, node) in component_nodes {
+        match name {
+            STANDARD_HEADER => header = reader.read_definition("Header", &node, "header")?,
+            STANDARD_TRAILER => trailer = reader.read_definition("Trailer", &node, "trailer")?,
+            _ => components.push(reader.read_definition(name, &node, &format!("component {}", name))?),
+        }
+    }
+
+    let mut messages = Vec::new();
+    for message in children(root, "messages", "message") {
+        if message.attribute("scenario").unwrap_or("base") != "base" {
+            continue;
+        }
+        let context = format!("message {}", message.attribute("name").unwrap_or("?"));
+        let (name, msgtype) = match (message.attribute("name"), message.attribute("msgType")) {
+            (Some(name), Some(msgtype)) => (name, msgtype),
+            (name, _) => {
+                reader.m_issues.push(missing_attribute(&context, "message", if name.is_none() { "name" } else { "msgType" }));
+                continue;
+            }
+        };
+        let msgcat = if message.attribute("category") == Some("Session") { "admin" } else { "app" };
+
+        let definition = match message.children().find(|n| n.has_tag_name("structure")) {
+            Some(structure) => reader.read_definition(name, &structure, &context)?,
+            None => Component::new(name.to_string()),
+        };
+        messages.push((Message::new(name.to_string(), msgtype.to_string(), msgcat.to_string()), definition));
+    }
+
+    Ok(Repository {
+        m_begin_string: begin_string,
+        m_fields: fields,
+        m_components: components,
+        m_messages: messages,
+        m_header: header,
+        m_trailer: trailer,
+        m_issues: reader.m_issues,
+    })
+}
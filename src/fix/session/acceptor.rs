@@ -0,0 +1,188 @@
+use std::collections::HashMap;
+use std::io::{ErrorKind, Read};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::{self, JoinHandle};
+use std::time;
+
+use crate::{Extrans, ExtransError, FIX};
+use crate::fix::FixFramer;
+use crate::ido::Ido;
+
+use super::connection::{Application, POLL_INTERVAL, SessionHandle};
+use super::{BEGIN_STRING, SENDER_COMP_ID, Session, SessionID, SessionRole, TARGET_COMP_ID};
+
+/// How long a new connection may take to send its first message.
+const FIRST_MESSAGE_TIMEOUT: time::Duration = time::Duration::from_secs(10);
+
+/// Blocking TCP connector accepting the sessions added to it on one address.
+///
+/// `start` binds the address and spawns a thread accepting connections, each run on a thread
+/// of its own. The first message received on a connection, normally the Logon(A), picks the
+/// session: its BeginString(8) and CompIDs, reversed, must be those of a session added and not
+/// already connected, otherwise the connection is closed. `stop`, also called on drop, logs
+/// out the connected sessions and joins the threads.
+pub struct Acceptor {
+    m_fix: Arc<FIX>,
+    m_address: String,
+    m_application: Arc<dyn Application>,
+    m_sessions: HashMap<SessionID, SessionHandle>,
+    m_local_addr: Option<SocketAddr>,
+    m_running: Arc<AtomicBool>,
+    m_thread: Option<JoinHandle<()>>,
+}
+
+impl Acceptor {
+    /// Creates a connector listening on `address` given as host:port, port 0 picks a free port.
+    pub fn new(fix: Arc<FIX>, address: &str, application: Arc<dyn Application>) -> Self {
+        Acceptor {
+            m_fix: fix,
+            m_address: address.to_string(),
+            m_application: application,
+            m_sessions: HashMap::new(),
+            m_local_addr: None,
+            m_running: Arc::new(AtomicBool::new(false)),
+            m_thread: None,
+        }
+    }
+
+    /// Adds an acceptor session. Sessions must be added before the acceptor is started.
+    pub fn add_session(&mut self, session: Session) -> Result<&SessionHandle, ExtransError> {
+        let session_id = session.get_session_id().clone();
+        if session.get_role() != SessionRole::ACCEPTOR {
+            return Err(ExtransError::SetupError(format!("session {} is not an acceptor", session_id)));
+        }
+        if self.m_thread.is_some() {
+            return Err(ExtransError::SetupError(format!("cannot add session {} to a started acceptor", session_id)));
+        }
+        if self.m_sessions.contains_key(&session_id) {
+            return Err(ExtransError::SetupError(format!("session {} is already added", session_id)));
+        }
+        Ok(self.m_sessions.entry(session_id).or_insert_with(|| SessionHandle::new(session, self.m_fix.clone())))
+    }
+
+    pub fn get_session(&self, session_id: &SessionID) -> Option<&SessionHandle> {
+        self.m_sessions.get(session_id)
+    }
+
+    pub fn get_sessions(&self) -> impl Iterator<Item = &SessionHandle> {
+        self.m_sessions.values()
+    }
+
+    /// Returns the address listened on once started.
+    pub fn get_local_addr(&self) -> Option<SocketAddr> {
+        self.m_local_addr
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.m_thread.is_some()
+    }
+
+    pub fn start(&mut self) -> Result<(), ExtransError> {
+        if self.m_thread.is_some() {
+            return Err(ExtransError::SetupError(format!("acceptor on {} is already started", self.m_address)));
+        }
+        let listener = TcpListener::bind(&self.m_address)?;
+        listener.set_nonblocking(true)?;
+        self.m_local_addr = Some(listener.local_addr()?);
+        log::info!("accepting {} sessions on {}", self.m_sessions.len(), listener.local_addr()?);
+        self.m_running.store(true, Ordering::SeqCst);
+
+        let fix = self.m_fix.clone();
+        let sessions = Arc::new(self.m_sessions.clone());
+        let application = self.m_application.clone();
+        let running = self.m_running.clone();
+        let thread = thread::Builder::new().name(format!("fix-acceptor-{}", listener.local_addr()?)).spawn(move || {
+            let mut connections: Vec<JoinHandle<()>> = Vec::new();
+            while running.load(Ordering::SeqCst) {
+                match listener.accept() {
+                    Ok((stream, peer)) => {
+                        log::info!("connection from {}", peer);
+                        let (fix, sessions, application, running) = (fix.clone(), sessions.clone(), application.clone(), running.clone());
+                        match thread::Builder::new().name(format!("fix-connection-{}", peer)).spawn(move || {
+                            Self::serve(stream, &fix, &sessions, application.as_ref(), &running)
+                        }) {
+                            Ok(connection) => connections.push(connection),
+                            Err(err) => log::error!("cannot serve connection from {}, {}", peer, err),
+                        }
+                    },
+                    Err(err) if err.kind() == ErrorKind::WouldBlock => thread::sleep(POLL_INTERVAL),
+                    Err(err) => log::warn!("accept failed, {}", err),
+                }
+                connections.retain(|connection| !connection.is_finished());
+            }
+            for connection in connections {
+                let _ = connection.join();
+            }
+        })?;
+        self.m_thread = Some(thread);
+        Ok(())
+    }
+
+    /// Waits for the first message of a connection, finds its session and runs it.
+    fn serve(stream: TcpStream, fix: &FIX, sessions: &HashMap<SessionID, SessionHandle>, application: &dyn Application, running: &AtomicBool) {
+        let peer = stream.peer_addr().map(|peer| peer.to_string()).unwrap_or_default();
+        let mut framer = FixFramer::new();
+        let first = match Self::read_first(&stream, &mut framer, fix, running) {
+            Ok(Some(message)) => message,
+            Ok(None) => return,
+            Err(err) => {
+                log::warn!("connection from {}: {}", peer, err);
+                return;
+            },
+        };
+
+        let field = |tag| first.get_string(&tag).unwrap_or_default();
+        let session_id = SessionID::new(&field(BEGIN_STRING), &field(TARGET_COMP_ID), &field(SENDER_COMP_ID));
+        let handle = match sessions.get(&session_id) {
+            Some(handle) => handle,
+            None => {
+                log::warn!("connection from {}: unknown session {}", peer, session_id);
+                return;
+            },
+        };
+        if let Err(err) = handle.attach(&stream) {
+            log::warn!("connection from {}: {}", peer, err);
+            return;
+        }
+        let _ = stream.set_nodelay(true);
+        handle.run(stream, framer, Some(first), application, running);
+    }
+
+    fn read_first(mut stream: &TcpStream, framer: &mut FixFramer, fix: &FIX, running: &AtomicBool) -> Result<Option<Ido>, ExtransError> {
+        stream.set_nonblocking(false)?;
+        stream.set_read_timeout(Some(POLL_INTERVAL))?;
+        let until = time::Instant::now() + FIRST_MESSAGE_TIMEOUT;
+        let mut buffer = [0u8; 4096];
+        while running.load(Ordering::SeqCst) && time::Instant::now() < until {
+            if let Some(frame) = framer.next_frame()? {
+                return fix.decode(&frame).map(Some);
+            }
+            match stream.read(&mut buffer) {
+                Ok(0) => return Ok(None),
+                Ok(len) => framer.push(&buffer[..len]),
+                Err(err) if matches!(err.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut | ErrorKind::Interrupted) => {},
+                Err(err) => return Err(err.into()),
+            }
+        }
+        Err(ExtransError::SessionError("no message received".to_string()))
+    }
+
+    /// Logs out the connected sessions, waiting for each counterparty to confirm or the logout
+    /// timeout, and stops accepting connections.
+    pub fn stop(&mut self) {
+        self.m_running.store(false, Ordering::SeqCst);
+        if let Some(thread) = self.m_thread.take() {
+            if thread.join().is_err() {
+                log::error!("acceptor thread panicked");
+            }
+        }
+    }
+}
+
+impl Drop for Acceptor {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
@@ -0,0 +1,17 @@
+use crate::ido::Ido;
+
+/// What the caller of a `Session` must do after feeding it an inbound message, a timer tick or
+/// a request. Actions are returned in the order they must be carried out.
+#[derive(Clone)]
+pub enum SessionAction {
+    /// Encode and send the message, its header already filled in by the session.
+    Send { msgtype: String, message: Ido },
+    /// Pass an inbound application message to the application.
+    Deliver { msgtype: String, message: Ido },
+    /// The session has logged on.
+    LoggedOn,
+    /// The session has logged out or lost its connection.
+    LoggedOut,
+    /// Close the transport connection.
+    Disconnect { reason: String },
+}
@@ -0,0 +1,252 @@
+use std::io::{ErrorKind, Read, Write};
+use std::net::{Shutdown, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time;
+
+use chrono::{DateTime, Utc};
+
+use crate::{Extrans, ExtransError, FIX};
+use crate::fix::FixFramer;
+use crate::ido::Ido;
+
+use super::{Session, SessionAction, SessionID, SessionState};
+
+/// How often a connection wakes up to drive the session timers when nothing is received.
+pub(super) const POLL_INTERVAL: time::Duration = time::Duration::from_millis(100);
+
+/// Receives the events of the sessions run by an `Initiator` or `Acceptor`. Callbacks are made
+/// from the connection threads, never while the session is locked, so a callback may send
+/// through the handle it is given.
+pub trait Application: Send + Sync {
+    fn on_logon(&self, _session: &SessionHandle) {}
+
+    fn on_logout(&self, _session: &SessionHandle) {}
+
+    /// Called with every application message received, in MsgSeqNum(34) order.
+    fn on_message(&self, session: &SessionHandle, msgtype: &str, message: &Ido);
+}
+
+/// Sleeps for `duration`, waking up early once `running` is cleared.
+pub(super) fn wait(duration: time::Duration, running: &AtomicBool) {
+    let until = time::Instant::now() + duration;
+    while running.load(Ordering::SeqCst) && time::Instant::now() < until {
+        std::thread::sleep(POLL_INTERVAL.min(until - time::Instant::now()));
+    }
+}
+
+struct SessionSlot {
+    m_session: Mutex<Session>,
+    m_stream: Mutex<Option<TcpStream>>,
+}
+
+/// Shared handle on a session run by a connector, used to send application messages to the
+/// counterparty and to query the session from any thread.
+#[derive(Clone)]
+pub struct SessionHandle {
+    m_slot: Arc<SessionSlot>,
+    m_fix: Arc<FIX>,
+    m_session_id: SessionID,
+}
+
+impl SessionHandle {
+    pub(super) fn new(session: Session, fix: Arc<FIX>) -> Self {
+        SessionHandle {
+            m_session_id: session.get_session_id().clone(),
+            m_slot: Arc::new(SessionSlot { m_session: Mutex::new(session), m_stream: Mutex::new(None) }),
+            m_fix: fix,
+        }
+    }
+
+    pub fn get_session_id(&self) -> &SessionID {
+        &self.m_session_id
+    }
+
+    pub fn get_state(&self) -> SessionState {
+        self.m_slot.m_session.lock().unwrap().get_state()
+    }
+
+    pub fn is_logged_on(&self) -> bool {
+        self.m_slot.m_session.lock().unwrap().is_logged_on()
+    }
+
+    pub fn get_next_sender_seq_num(&self) -> u64 {
+        self.m_slot.m_session.lock().unwrap().get_next_sender_seq_num()
+    }
+
+    pub fn get_next_target_seq_num(&self) -> u64 {
+        self.m_slot.m_session.lock().unwrap().get_next_target_seq_num()
+    }
+
+    pub fn is_in_schedule(&self) -> bool {
+        self.m_slot.m_session.lock().unwrap().is_in_schedule(Utc::now())
+    }
+
+    /// Returns true while a transport connection is attached to the session.
+    pub fn is_connected(&self) -> bool {
+        self.m_slot.m_stream.lock().unwrap().is_some()
+    }
+
+    /// Sends an application message. Fails if the session is not logged on or the message
+    /// cannot be encoded or written. A message that cannot be encoded is refused before it takes
+    /// a MsgSeqNum(34), so it is never stored for replay.
+    pub fn send(&self, msgtype: &str, message: Ido) -> Result<(), ExtransError> {
+        let mut session = self.m_slot.m_session.lock().unwrap();
+        let now = Utc::now();
+        let data = self.m_fix.encode(&session.prepare(&message, now)?, msgtype)?;
+        session.send(msgtype, message, now)?;
+        self.write(&data)
+    }
+
+    /// Starts a logout, the connection is closed once the counterparty confirms it.
+    pub fn logout(&self, text: Option<&str>) {
+        self.process(|session, now| session.logout(text, now));
+    }
+
+    /// Runs `event` on the locked session and writes the messages it sends, returning the
+    /// actions left for the caller.
+    fn process(&self, event: impl FnOnce(&mut Session, DateTime<Utc>) -> Vec<SessionAction>) -> Vec<SessionAction> {
+        let mut session = self.m_slot.m_session.lock().unwrap();
+        let actions = event(&mut session, Utc::now());
+        match self.write_actions(actions) {
+            Ok(actions) => actions,
+            Err(err) => {
+                log::error!("session {}: {}", self.m_session_id, err);
+                let mut actions = session.on_disconnect(Utc::now());
+                actions.push(SessionAction::Disconnect { reason: err.to_string() });
+                actions
+            },
+        }
+    }
+
+    /// Encodes and writes the Send actions, in order, returning the other actions. Must be called
+    /// with the session locked so that messages go out in MsgSeqNum(34) order.
+    fn write_actions(&self, actions: Vec<SessionAction>) -> Result<Vec<SessionAction>, ExtransError> {
+        let mut rest = Vec::new();
+        for action in actions {
+            match action {
+                SessionAction::Send { msgtype, message } => self.write(&self.m_fix.encode(&message, &msgtype)?)?,
+                other => rest.push(other),
+            }
+        }
+        Ok(rest)
+    }
+
+    fn write(&self, data: &[u8]) -> Result<(), ExtransError> {
+        match self.m_slot.m_stream.lock().unwrap().as_mut() {
+            Some(stream) => stream.write_all(data).map_err(|e| ExtransError::SessionError(format!("write failed: {}", e))),
+            None => Err(ExtransError::SessionError("not connected".to_string())),
+        }
+    }
+
+    /// Carries out the actions left by `process`, returning false once the connection is to
+    /// be closed.
+    fn dispatch(&self, actions: Vec<SessionAction>, application: &dyn Application) -> bool {
+        let mut connected = true;
+        for action in actions {
+            match action {
+                SessionAction::Deliver { msgtype, message } => application.on_message(self, &msgtype, &message),
+                SessionAction::LoggedOn => application.on_logon(self),
+                SessionAction::LoggedOut => application.on_logout(self),
+                SessionAction::Disconnect { reason } => {
+                    log::info!("session {}: disconnecting, {}", self.m_session_id, reason);
+                    connected = false;
+                },
+                SessionAction::Send { .. } => {},
+            }
+        }
+        connected
+    }
+
+    /// Attaches a connection, failing if the session already has one.
+    pub(super) fn attach(&self, stream: &TcpStream) -> Result<(), ExtransError> {
+        let mut attached = self.m_slot.m_stream.lock().unwrap();
+        if attached.is_some() {
+            return Err(ExtransError::SessionError(format!("session {} is already connected", self.m_session_id)));
+        }
+        *attached = Some(stream.try_clone()?);
+        Ok(())
+    }
+
+    fn detach(&self) {
+        if let Some(stream) = self.m_slot.m_stream.lock().unwrap().take() {
+            let _ = stream.shutdown(Shutdown::Both);
+        }
+    }
+
+    /// Runs the session over an attached connection until it is closed. `first` is a message
+    /// already taken from the stream, as the acceptor does to identify the session, and
+    /// `framer` holds whatever was read after it. Once `running` is cleared the session logs
+    /// out.
+    pub(super) fn run(&self, mut stream: TcpStream, mut framer: FixFramer, first: Option<Ido>, application: &dyn Application, running: &AtomicBool) {
+        if let Err(err) = stream.set_read_timeout(Some(POLL_INTERVAL)) {
+            log::error!("session {}: {}", self.m_session_id, err);
+            self.detach();
+            return;
+        }
+
+        let mut connected = self.dispatch(self.process(|session, now| session.on_connect(now)), application);
+        if let (true, Some(message)) = (connected, first) {
+            connected = self.dispatch(self.process(|session, now| session.on_message(message, now)), application);
+        }
+
+        let mut logout_requested = false;
+        let mut buffer = [0u8; 8192];
+        while connected {
+            match stream.read(&mut buffer) {
+                Ok(0) => {
+                    log::info!("session {}: connection closed by the counterparty", self.m_session_id);
+                    break;
+                },
+                Ok(len) => {
+                    framer.push(&buffer[..len]);
+                    connected = self.on_data(&mut framer, application);
+                },
+                Err(err) if matches!(err.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut | ErrorKind::Interrupted) => {},
+                Err(err) => {
+                    log::warn!("session {}: read failed, {}", self.m_session_id, err);
+                    break;
+                },
+            }
+            if !connected {
+                break;
+            }
+
+            if !running.load(Ordering::SeqCst) && !logout_requested {
+                logout_requested = true;
+                connected = self.dispatch(self.process(|session, now| session.logout(Some("shutting down"), now)), application);
+            }
+            connected = connected && self.dispatch(self.process(|session, now| session.on_timer(now)), application);
+            connected = connected && self.get_state() != SessionState::DISCONNECTED;
+        }
+
+        let actions = self.m_slot.m_session.lock().unwrap().on_disconnect(Utc::now());
+        self.detach();
+        self.dispatch(actions, application);
+    }
+
+    /// Decodes and processes the complete frames received. Garbled messages are logged and
+    /// dropped, a later gap detection asks for them again.
+    fn on_data(&self, framer: &mut FixFramer, application: &dyn Application) -> bool {
+        loop {
+            let frame = match framer.next_frame() {
+                Ok(Some(frame)) => frame,
+                Ok(None) => return true,
+                Err(err) => {
+                    log::warn!("session {}: {}", self.m_session_id, err);
+                    continue;
+                },
+            };
+            let message = match self.m_fix.decode(&frame) {
+                Ok(message) => message,
+                Err(err) => {
+                    log::warn!("session {}: dropping garbled message, {}", self.m_session_id, err);
+                    continue;
+                },
+            };
+            if !self.dispatch(self.process(|session, now| session.on_message(message, now)), application) {
+                return false;
+            }
+        }
+    }
+}
@@ -0,0 +1,314 @@
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use chrono::DateTime;
+
+use crate::ExtransError;
+use crate::ido::{Ido, IdoItem, IdoItemType, IdoKeyT};
+
+use super::message_store::MessageStore;
+use super::session_id::SessionID;
+
+/// Length of the seqnums line, two 20 digit numbers, a space and a newline.
+const SEQNUMS_LINE_LENGTH: usize = 42;
+
+fn store_error(path: &Path, err: std::io::Error) -> ExtransError {
+    ExtransError::StoreError(format!("{}: {}", path.display(), err))
+}
+
+fn corrupt(what: &str) -> ExtransError {
+    ExtransError::StoreError(format!("corrupt message store record: {}", what))
+}
+
+/// Message store kept in two files in a directory, named after the session:
+///
+/// - `<BeginString>-<SenderCompID>-<TargetCompID>.seqnums` holds a single fixed width line with
+///   the next sender and target MsgSeqNum(34), rewritten in place each time either changes.
+/// - `<BeginString>-<SenderCompID>-<TargetCompID>.body` holds the sent messages appended, each
+///   record a little endian length followed by the MsgSeqNum, MsgType(35) and typed Ido content.
+///
+/// Every write is synced to disk before it is reported done. Opening the store reads both files
+/// back, so a restarted session resumes at the sequence numbers it stopped at and can still
+/// resend what it sent before. A record torn by a crash is cut off, and a seqnums file of
+/// several lines, as earlier versions appended, is compacted to its last complete line. Only
+/// `reset` truncates the body.
+pub struct FileStore {
+    m_seqnums_path: PathBuf,
+    m_body_path: PathBuf,
+    m_seqnums: File,
+    m_body: File,
+    m_next_sender_seq_num: u64,
+    m_next_target_seq_num: u64,
+    m_offsets: HashMap<u64, (u64, usize)>,
+}
+
+impl FileStore {
+    /// Opens the store of `session_id` in `directory`, creating both if needed.
+    pub fn new(directory: &str, session_id: &SessionID) -> Result<Self, ExtransError> {
+        let directory = Path::new(directory);
+        fs::create_dir_all(directory).map_err(|e| store_error(directory, e))?;
+        let name = format!("{}-{}-{}", session_id.get_begin_string(), session_id.get_sender_comp_id(), session_id.get_target_comp_id());
+        let seqnums_path = directory.join(format!("{}.seqnums", name));
+        let body_path = directory.join(format!("{}.body", name));
+
+        let mut store = FileStore {
+            m_seqnums: OpenOptions::new().create(true).truncate(false).read(true).write(true).open(&seqnums_path)
+                .map_err(|e| store_error(&seqnums_path, e))?,
+            m_body: OpenOptions::new().create(true).read(true).append(true).open(&body_path).map_err(|e| store_error(&body_path, e))?,
+            m_seqnums_path: seqnums_path,
+            m_body_path: body_path,
+            m_next_sender_seq_num: 1,
+            m_next_target_seq_num: 1,
+            m_offsets: HashMap::new(),
+        };
+        store.recover_seqnums()?;
+        store.recover_body()?;
+        Ok(store)
+    }
+
+    fn read_all(file: &mut File, path: &Path) -> Result<Vec<u8>, ExtransError> {
+        let mut data = Vec::new();
+        file.seek(SeekFrom::Start(0)).and_then(|_| file.read_to_end(&mut data)).map_err(|e| store_error(path, e))?;
+        Ok(data)
+    }
+
+    fn recover_seqnums(&mut self) -> Result<(), ExtransError> {
+        let data = Self::read_all(&mut self.m_seqnums, &self.m_seqnums_path)?;
+        let complete = data.iter().rposition(|b| *b == b'\n').map_or(0, |pos| pos + 1);
+        if complete < data.len() {
+            log::warn!("{}: discarding incomplete last line", self.m_seqnums_path.display());
+        }
+
+        let last = String::from_utf8_lossy(&data[..complete]).lines().rev().find_map(|line| {
+            let (sender, target) = line.split_once(' ')?;
+            Some((sender.parse::<u64>().ok()?, target.parse::<u64>().ok()?))
+        });
+        if let Some((sender, target)) = last {
+            self.m_next_sender_seq_num = sender;
+            self.m_next_target_seq_num = target;
+        }
+        if data.len() != SEQNUMS_LINE_LENGTH {
+            self.compact_seqnums()?;
+        }
+        Ok(())
+    }
+
+    /// Replaces the seqnums file by one holding only the current line, through a temporary file
+    /// renamed over it so that a crash leaves either the old or the new content.
+    fn compact_seqnums(&mut self) -> Result<(), ExtransError> {
+        let path = self.m_seqnums_path.with_extension("seqnums.tmp");
+        let mut file = OpenOptions::new().create(true).truncate(true).read(true).write(true).open(&path).map_err(|e| store_error(&path, e))?;
+        file.write_all(self.seqnums_line().as_bytes()).and_then(|_| file.sync_all()).map_err(|e| store_error(&path, e))?;
+        fs::rename(&path, &self.m_seqnums_path).map_err(|e| store_error(&self.m_seqnums_path, e))?;
+        // the rename is durable once the directory is synced, where directories can be opened
+        if let Some((directory, Ok(file))) = self.m_seqnums_path.parent().map(|directory| (directory, File::open(directory))) {
+            file.sync_all().map_err(|e| store_error(directory, e))?;
+        }
+        self.m_seqnums = file;
+        Ok(())
+    }
+
+    fn recover_body(&mut self) -> Result<(), ExtransError> {
+        let data = Self::read_all(&mut self.m_body, &self.m_body_path)?;
+        let mut pos = 0;
+        while pos + 12 <= data.len() {
+            let len = u32::from_le_bytes(data[pos..pos + 4].try_into().unwrap()) as usize;
+            if len < 8 || pos + 4 + len > data.len() {
+                break;
+            }
+            let seq_num = u64::from_le_bytes(data[pos + 4..pos + 12].try_into().unwrap());
+            self.m_offsets.insert(seq_num, ((pos + 4) as u64, len));
+            pos += 4 + len;
+        }
+        if pos < data.len() {
+            log::warn!("{}: discarding incomplete last record", self.m_body_path.display());
+            self.m_body.set_len(pos as u64).map_err(|e| store_error(&self.m_body_path, e))?;
+        }
+        Ok(())
+    }
+
+    fn seqnums_line(&self) -> String {
+        format!("{:020} {:020}\n", self.m_next_sender_seq_num, self.m_next_target_seq_num)
+    }
+
+    /// Overwrites the seqnums line in place, it keeps the same length whatever the values.
+    fn write_seqnums(&mut self) -> Result<(), ExtransError> {
+        let line = self.seqnums_line();
+        self.m_seqnums.seek(SeekFrom::Start(0))
+            .and_then(|_| self.m_seqnums.write_all(line.as_bytes()))
+            .and_then(|_| self.m_seqnums.sync_data())
+            .map_err(|e| store_error(&self.m_seqnums_path, e))
+    }
+}
+
+impl MessageStore for FileStore {
+    fn get_next_sender_seq_num(&self) -> u64 {
+        self.m_next_sender_seq_num
+    }
+
+    fn get_next_target_seq_num(&self) -> u64 {
+        self.m_next_target_seq_num
+    }
+
+    fn set_next_sender_seq_num(&mut self, seq_num: u64) -> Result<(), ExtransError> {
+        self.m_next_sender_seq_num = seq_num;
+        self.write_seqnums()
+    }
+
+    fn set_next_target_seq_num(&mut self, seq_num: u64) -> Result<(), ExtransError> {
+        self.m_next_target_seq_num = seq_num;
+        self.write_seqnums()
+    }
+
+    fn save(&mut self, seq_num: u64, msgtype: &str, message: &Ido) -> Result<(), ExtransError> {
+        let mut record = Vec::new();
+        record.extend_from_slice(&seq_num.to_le_bytes());
+        write_bytes(&mut record, msgtype.as_bytes());
+        write_ido(&mut record, message);
+
+        let offset = self.m_body.metadata().map_err(|e| store_error(&self.m_body_path, e))?.len() + 4;
+        let mut data = Vec::with_capacity(record.len() + 4);
+        data.extend_from_slice(&(record.len() as u32).to_le_bytes());
+        data.extend_from_slice(&record);
+        self.m_body.write_all(&data).and_then(|_| self.m_body.sync_data()).map_err(|e| store_error(&self.m_body_path, e))?;
+        self.m_offsets.insert(seq_num, (offset, record.len()));
+        Ok(())
+    }
+
+    fn get(&self, seq_num: u64) -> Result<Option<(String, Ido)>, ExtransError> {
+        let (offset, len) = match self.m_offsets.get(&seq_num) {
+            Some(location) => *location,
+            None => return Ok(None),
+        };
+        let mut record = vec![0; len];
+        let mut body = &self.m_body;
+        body.seek(SeekFrom::Start(offset)).and_then(|_| body.read_exact(&mut record)).map_err(|e| store_error(&self.m_body_path, e))?;
+
+        let mut reader = RecordReader { m_data: &record, m_pos: 8 };
+        let msgtype = String::from_utf8(reader.read_bytes()?.to_vec()).map_err(|_| corrupt("MsgType is not UTF-8"))?;
+        let message = reader.read_ido()?;
+        Ok(Some((msgtype, message)))
+    }
+
+    fn reset(&mut self) -> Result<(), ExtransError> {
+        self.m_body.set_len(0).and_then(|_| self.m_body.sync_data()).map_err(|e| store_error(&self.m_body_path, e))?;
+        self.m_offsets.clear();
+        self.m_next_sender_seq_num = 1;
+        self.m_next_target_seq_num = 1;
+        self.write_seqnums()
+    }
+}
+
+fn write_bytes(buffer: &mut Vec<u8>, value: &[u8]) {
+    buffer.extend_from_slice(&(value.len() as u32).to_le_bytes());
+    buffer.extend_from_slice(value);
+}
+
+/// Writes the items of an Ido by ascending key, arrays recursively.
+fn write_ido(buffer: &mut Vec<u8>, ido: &Ido) {
+    let mut keys: Vec<IdoKeyT> = ido.keys().copied().collect();
+    keys.sort();
+    buffer.extend_from_slice(&(keys.len() as u32).to_le_bytes());
+    for key in keys {
+        let item = ido.get_item_ref(&key).unwrap();
+        buffer.extend_from_slice(&key.to_le_bytes());
+        match item.get_type() {
+            IdoItemType::STRING => {
+                buffer.push(0);
+                write_bytes(buffer, item.m_string.as_bytes());
+            },
+            IdoItemType::INTEGER => {
+                buffer.push(1);
+                buffer.extend_from_slice(&item.m_integer.to_le_bytes());
+            },
+            IdoItemType::FLOAT => {
+                buffer.push(2);
+                buffer.extend_from_slice(&item.m_float.to_le_bytes());
+            },
+            IdoItemType::DATETIME => {
+                buffer.push(3);
+                buffer.extend_from_slice(&item.m_datetime.timestamp().to_le_bytes());
+                buffer.extend_from_slice(&item.m_datetime.timestamp_subsec_nanos().to_le_bytes());
+            },
+            IdoItemType::BYTES => {
+                buffer.push(4);
+                write_bytes(buffer, &item.m_bytes);
+            },
+            IdoItemType::ARRAY => {
+                buffer.push(5);
+                buffer.extend_from_slice(&(item.m_array.len() as u32).to_le_bytes());
+                for entry in &item.m_array {
+                    write_ido(buffer, entry);
+                }
+            },
+        }
+    }
+}
+
+struct RecordReader<'a> {
+    m_data: &'a [u8],
+    m_pos: usize,
+}
+
+impl<'a> RecordReader<'a> {
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], ExtransError> {
+        let bytes = self.m_data.get(self.m_pos..self.m_pos + N).ok_or_else(|| corrupt("truncated"))?;
+        self.m_pos += N;
+        Ok(bytes.try_into().unwrap())
+    }
+
+    fn read_u32(&mut self) -> Result<u32, ExtransError> {
+        Ok(u32::from_le_bytes(self.take()?))
+    }
+
+    fn read_bytes(&mut self) -> Result<&'a [u8], ExtransError> {
+        let len = self.read_u32()? as usize;
+        let bytes = self.m_data.get(self.m_pos..self.m_pos + len).ok_or_else(|| corrupt("truncated"))?;
+        self.m_pos += len;
+        Ok(bytes)
+    }
+
+    fn read_ido(&mut self) -> Result<Ido, ExtransError> {
+        let mut ido = Ido::new();
+        for _ in 0..self.read_u32()? {
+            let key = IdoKeyT::from_le_bytes(self.take()?);
+            let mut item = IdoItem::new();
+            match self.take::<1>()?[0] {
+                0 => {
+                    item.m_type = IdoItemType::STRING;
+                    item.m_string = String::from_utf8(self.read_bytes()?.to_vec()).map_err(|_| corrupt("string is not UTF-8"))?;
+                },
+                1 => {
+                    item.m_type = IdoItemType::INTEGER;
+                    item.m_integer = i64::from_le_bytes(self.take()?);
+                },
+                2 => {
+                    item.m_type = IdoItemType::FLOAT;
+                    item.m_float = f64::from_le_bytes(self.take()?);
+                },
+                3 => {
+                    item.m_type = IdoItemType::DATETIME;
+                    let seconds = i64::from_le_bytes(self.take()?);
+                    let nanoseconds = u32::from_le_bytes(self.take()?);
+                    item.m_datetime = DateTime::from_timestamp(seconds, nanoseconds).ok_or_else(|| corrupt("invalid timestamp"))?;
+                },
+                4 => {
+                    item.m_type = IdoItemType::BYTES;
+                    item.m_bytes = self.read_bytes()?.to_vec();
+                },
+                5 => {
+                    item.m_type = IdoItemType::ARRAY;
+                    for _ in 0..self.read_u32()? {
+                        item.m_array.push(self.read_ido()?);
+                    }
+                    item.m_integer = item.m_array.len() as i64;
+                },
+                other => return Err(corrupt(&format!("unknown item type {}", other))),
+            }
+            ido.set_item(&key, item);
+        }
+        Ok(ido)
+    }
+}
@@ -0,0 +1,110 @@
+use std::net::TcpStream;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::{self, JoinHandle};
+use std::time;
+
+use crate::{ExtransError, FIX};
+use crate::fix::FixFramer;
+
+use super::connection::{self, Application, POLL_INTERVAL, SessionHandle};
+use super::{Session, SessionRole};
+
+/// Blocking TCP connector for an initiator session.
+///
+/// `start` spawns a thread that connects to the counterparty, runs the session over the
+/// connection until it closes, then waits for the reconnect interval and connects again. No
+/// connection is made outside the session schedule. `stop`, also called on drop, logs the
+/// session out and joins the thread.
+pub struct Initiator {
+    m_handle: SessionHandle,
+    m_address: String,
+    m_application: Arc<dyn Application>,
+    m_reconnect_interval: time::Duration,
+    m_running: Arc<AtomicBool>,
+    m_thread: Option<JoinHandle<()>>,
+}
+
+impl Initiator {
+    /// Creates a connector for `session`, which must be an initiator, connecting to `address`
+    /// given as host:port.
+    pub fn new(fix: Arc<FIX>, session: Session, address: &str, application: Arc<dyn Application>) -> Result<Self, ExtransError> {
+        if session.get_role() != SessionRole::INITIATOR {
+            return Err(ExtransError::SetupError(format!("session {} is not an initiator", session.get_session_id())));
+        }
+        Ok(Initiator {
+            m_handle: SessionHandle::new(session, fix),
+            m_address: address.to_string(),
+            m_application: application,
+            m_reconnect_interval: time::Duration::from_secs(30),
+            m_running: Arc::new(AtomicBool::new(false)),
+            m_thread: None,
+        })
+    }
+
+    /// Sets how long to wait before connecting again after a connection closes or fails,
+    /// 30 seconds by default.
+    pub fn set_reconnect_interval(&mut self, interval: time::Duration) {
+        self.m_reconnect_interval = interval;
+    }
+
+    pub fn get_session(&self) -> &SessionHandle {
+        &self.m_handle
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.m_thread.is_some()
+    }
+
+    pub fn start(&mut self) -> Result<(), ExtransError> {
+        if self.m_thread.is_some() {
+            return Err(ExtransError::SetupError(format!("initiator of session {} is already started", self.m_handle.get_session_id())));
+        }
+        self.m_running.store(true, Ordering::SeqCst);
+
+        let handle = self.m_handle.clone();
+        let address = self.m_address.clone();
+        let application = self.m_application.clone();
+        let reconnect_interval = self.m_reconnect_interval;
+        let running = self.m_running.clone();
+        let thread = thread::Builder::new().name(format!("fix-initiator-{}", handle.get_session_id())).spawn(move || {
+            while running.load(Ordering::SeqCst) {
+                if !handle.is_in_schedule() {
+                    connection::wait(POLL_INTERVAL * 10, &running);
+                    continue;
+                }
+                match TcpStream::connect(&address) {
+                    Ok(stream) => {
+                        log::info!("session {}: connected to {}", handle.get_session_id(), address);
+                        let _ = stream.set_nodelay(true);
+                        match handle.attach(&stream) {
+                            Ok(()) => handle.run(stream, FixFramer::new(), None, application.as_ref(), &running),
+                            Err(err) => log::error!("{}", err),
+                        }
+                    },
+                    Err(err) => log::warn!("session {}: cannot connect to {}, {}", handle.get_session_id(), address, err),
+                }
+                connection::wait(reconnect_interval, &running);
+            }
+        })?;
+        self.m_thread = Some(thread);
+        Ok(())
+    }
+
+    /// Logs the session out, waiting for the counterparty to confirm or the logout timeout,
+    /// and stops reconnecting.
+    pub fn stop(&mut self) {
+        self.m_running.store(false, Ordering::SeqCst);
+        if let Some(thread) = self.m_thread.take() {
+            if thread.join().is_err() {
+                log::error!("session {}: initiator thread panicked", self.m_handle.get_session_id());
+            }
+        }
+    }
+}
+
+impl Drop for Initiator {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
@@ -0,0 +1,85 @@
+use std::collections::BTreeMap;
+
+use crate::ExtransError;
+use crate::ido::Ido;
+
+/// Keeps the state a session needs to survive a reconnect: the next MsgSeqNum(34) to send and
+/// to expect, and the messages sent so that ResendRequest(2)s can be satisfied. Only application
+/// messages are saved, admin messages are gap filled rather than resent.
+pub trait MessageStore: Send {
+    fn get_next_sender_seq_num(&self) -> u64;
+
+    fn get_next_target_seq_num(&self) -> u64;
+
+    fn set_next_sender_seq_num(&mut self, seq_num: u64) -> Result<(), ExtransError>;
+
+    fn set_next_target_seq_num(&mut self, seq_num: u64) -> Result<(), ExtransError>;
+
+    /// Saves a sent message, its header filled in, under its MsgSeqNum(34).
+    fn save(&mut self, seq_num: u64, msgtype: &str, message: &Ido) -> Result<(), ExtransError>;
+
+    /// Returns the MsgType(35) and content of a saved message.
+    fn get(&self, seq_num: u64) -> Result<Option<(String, Ido)>, ExtransError>;
+
+    /// Discards the saved messages and starts both sequence numbers again at 1.
+    fn reset(&mut self) -> Result<(), ExtransError>;
+}
+
+/// Message store held in memory, lost when the process ends.
+pub struct MemoryStore {
+    m_next_sender_seq_num: u64,
+    m_next_target_seq_num: u64,
+    m_messages: BTreeMap<u64, (String, Ido)>,
+}
+
+impl Default for MemoryStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        MemoryStore {
+            m_next_sender_seq_num: 1,
+            m_next_target_seq_num: 1,
+            m_messages: BTreeMap::new(),
+        }
+    }
+}
+
+impl MessageStore for MemoryStore {
+    fn get_next_sender_seq_num(&self) -> u64 {
+        self.m_next_sender_seq_num
+    }
+
+    fn get_next_target_seq_num(&self) -> u64 {
+        self.m_next_target_seq_num
+    }
+
+    fn set_next_sender_seq_num(&mut self, seq_num: u64) -> Result<(), ExtransError> {
+        self.m_next_sender_seq_num = seq_num;
+        Ok(())
+    }
+
+    fn set_next_target_seq_num(&mut self, seq_num: u64) -> Result<(), ExtransError> {
+        self.m_next_target_seq_num = seq_num;
+        Ok(())
+    }
+
+    fn save(&mut self, seq_num: u64, msgtype: &str, message: &Ido) -> Result<(), ExtransError> {
+        self.m_messages.insert(seq_num, (msgtype.to_string(), message.clone()));
+        Ok(())
+    }
+
+    fn get(&self, seq_num: u64) -> Result<Option<(String, Ido)>, ExtransError> {
+        Ok(self.m_messages.get(&seq_num).cloned())
+    }
+
+    fn reset(&mut self) -> Result<(), ExtransError> {
+        self.m_next_sender_seq_num = 1;
+        self.m_next_target_seq_num = 1;
+        self.m_messages.clear();
+        Ok(())
+    }
+}
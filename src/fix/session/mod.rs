@@ -0,0 +1,775 @@
+mod acceptor;
+mod action;
+mod connection;
+mod file_store;
+mod initiator;
+mod message_store;
+mod schedule;
+mod session_id;
+mod settings;
+mod state;
+
+pub use acceptor::Acceptor;
+pub use action::SessionAction;
+pub use connection::{Application, SessionHandle};
+pub use file_store::FileStore;
+pub use initiator::Initiator;
+pub use message_store::{MemoryStore, MessageStore};
+pub use schedule::SessionSchedule;
+pub use session_id::SessionID;
+pub use settings::SessionSettings;
+pub use state::{SessionRole, SessionState};
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Duration, Utc};
+
+use crate::ExtransError;
+use crate::fix::field::FieldType;
+use crate::ido::{Ido, IdoKeyT};
+use crate::properties::Properties;
+
+const BEGIN_SEQ_NO: IdoKeyT = 7;
+const BEGIN_STRING: IdoKeyT = 8;
+const END_SEQ_NO: IdoKeyT = 16;
+const MSG_SEQ_NUM: IdoKeyT = 34;
+const MSG_TYPE: IdoKeyT = 35;
+const NEW_SEQ_NO: IdoKeyT = 36;
+const POSS_DUP_FLAG: IdoKeyT = 43;
+const REF_SEQ_NUM: IdoKeyT = 45;
+const SENDER_COMP_ID: IdoKeyT = 49;
+const SENDING_TIME: IdoKeyT = 52;
+const TARGET_COMP_ID: IdoKeyT = 56;
+const TEXT: IdoKeyT = 58;
+const ENCRYPT_METHOD: IdoKeyT = 98;
+const HEART_BT_INT: IdoKeyT = 108;
+const TEST_REQ_ID: IdoKeyT = 112;
+const ORIG_SENDING_TIME: IdoKeyT = 122;
+const RESET_SEQ_NUM_FLAG: IdoKeyT = 141;
+const GAP_FILL_FLAG: IdoKeyT = 123;
+const REF_TAG_ID: IdoKeyT = 371;
+const REF_MSG_TYPE: IdoKeyT = 372;
+const SESSION_REJECT_REASON: IdoKeyT = 373;
+
+const HEARTBEAT: &str = "0";
+const TEST_REQUEST: &str = "1";
+const RESEND_REQUEST: &str = "2";
+const REJECT: &str = "3";
+const SEQUENCE_RESET: &str = "4";
+const LOGOUT: &str = "5";
+const LOGON: &str = "A";
+
+/// Longest HeartBtInt(108) in seconds accepted from a counterparty, one day.
+const MAX_HEARTBEAT_INTERVAL: i64 = 24 * 60 * 60;
+
+/// SessionRejectReason(373) values.
+const REQUIRED_TAG_MISSING: i64 = 1;
+const VALUE_IS_INCORRECT: i64 = 5;
+const SENDING_TIME_ACCURACY_PROBLEM: i64 = 10;
+
+/// Reads an integer field, accepting the string form an Ido holds when the field was decoded
+/// without a dictionary definition.
+fn get_integer(ido: &Ido, tag: IdoKeyT) -> Option<i64> {
+    ido.get_i64(&tag).or_else(|| ido.get_string(&tag)?.parse().ok())
+}
+
+/// Reads a BOOLEAN field, absent fields are false.
+fn get_flag(ido: &Ido, tag: IdoKeyT) -> bool {
+    ido.get_string(&tag).map(|value| value == "Y").or_else(|| ido.get_i64(&tag).map(|value| value != 0)).unwrap_or(false)
+}
+
+/// Reads a UTCTIMESTAMP field, accepting the string form as `get_integer` does.
+fn get_timestamp(ido: &Ido, tag: IdoKeyT) -> Option<DateTime<Utc>> {
+    ido.get_datetime(&tag).or_else(|| FieldType::UTCTIMESTAMP.parse_datetime(&ido.get_string(&tag)?))
+}
+
+/// Admin messages are never resent, a SequenceReset-GapFill(4) takes their place.
+fn is_admin_msgtype(msgtype: &str) -> bool {
+    matches!(msgtype, HEARTBEAT | TEST_REQUEST | RESEND_REQUEST | REJECT | SEQUENCE_RESET | LOGOUT | LOGON)
+}
+
+/// Transport agnostic FIX session engine.
+///
+/// The session is a state machine driving Logon(A), Heartbeat(0), TestRequest(1) and Logout(5).
+/// It is fed the transport events, decoded inbound messages and timer ticks, each with the
+/// current time, and returns the `SessionAction`s to carry out: messages to encode and send,
+/// application messages to deliver and connections to close. It performs no I/O and reads no
+/// clock itself, so it can be driven entirely in memory.
+///
+/// Outbound messages get BeginString(8), SenderCompID(49), TargetCompID(56), MsgSeqNum(34)
+/// and SendingTime(52) filled in. Heartbeats are sent when nothing has been sent for HeartBtInt
+/// seconds, a TestRequest(1) when nothing has been received for HeartBtInt plus 20%, and the
+/// connection is dropped when the TestRequest goes unanswered for as long again.
+///
+/// Inbound MsgSeqNum(34) values are checked against the expected one. A gap queues the message
+/// and sends a ResendRequest(2); queued messages are processed once the gap is filled. A value
+/// lower than expected ends the session unless PossDupFlag(43) is set, in which case the message
+/// is ignored as a duplicate. Possible duplicates other than SequenceReset(4) must carry an
+/// OrigSendingTime(122) no later than their SendingTime, otherwise they are rejected with a
+/// Reject(3). Inbound ResendRequests
+/// are answered by replaying the application messages sent, flagged as possible duplicates,
+/// with admin messages and messages no longer held replaced by SequenceReset-GapFill(4).
+///
+/// Sequence numbers and sent application messages are kept in a `MessageStore`, in memory
+/// unless the session is given another store, e.g. a `FileStore` to resume after a restart.
+pub struct Session {
+    m_session_id: SessionID,
+    m_role: SessionRole,
+    m_state: SessionState,
+    m_heartbeat_interval: i64,
+    m_logon_timeout: Duration,
+    m_logout_timeout: Duration,
+    m_store: Box<dyn MessageStore>,
+    m_reset_on_logon: bool,
+    m_schedule: Option<SessionSchedule>,
+    m_last_sent: DateTime<Utc>,
+    m_last_received: DateTime<Utc>,
+    m_state_changed: DateTime<Utc>,
+    m_test_request: Option<(String, DateTime<Utc>)>,
+    m_test_request_counter: u64,
+    m_queue: BTreeMap<u64, Ido>,
+    m_queue_limit: usize,
+    m_resend_end: Option<u64>,
+}
+
+impl Session {
+    /// Creates a disconnected session keeping its sequence numbers and sent messages in memory.
+    /// `heartbeat_interval` is the HeartBtInt(108) in seconds an initiator proposes, an acceptor
+    /// adopts the interval of the Logon it receives.
+    pub fn new(session_id: SessionID, role: SessionRole, heartbeat_interval: i64) -> Self {
+        Self::with_store(session_id, role, heartbeat_interval, Box::new(MemoryStore::new()))
+    }
+
+    /// Creates a disconnected session resuming from the sequence numbers and sent messages held
+    /// by `store`.
+    pub fn with_store(session_id: SessionID, role: SessionRole, heartbeat_interval: i64, store: Box<dyn MessageStore>) -> Self {
+        Session {
+            m_session_id: session_id,
+            m_role: role,
+            m_state: SessionState::DISCONNECTED,
+            m_heartbeat_interval: heartbeat_interval,
+            m_logon_timeout: Duration::seconds(10),
+            m_logout_timeout: Duration::seconds(10),
+            m_store: store,
+            m_reset_on_logon: false,
+            m_schedule: None,
+            m_last_sent: DateTime::<Utc>::MIN_UTC,
+            m_last_received: DateTime::<Utc>::MIN_UTC,
+            m_state_changed: DateTime::<Utc>::MIN_UTC,
+            m_test_request: None,
+            m_test_request_counter: 0,
+            m_queue: BTreeMap::new(),
+            m_queue_limit: 1000,
+            m_resend_end: None,
+        }
+    }
+
+    /// Configures the session from properties:
+    ///
+    /// - `fix_store_path`: directory of a `FileStore` keeping the sequence numbers and sent
+    ///   messages across restarts. Without it they are kept in memory.
+    /// - `fix_reset_on_logon`: true to start both sequence numbers again at 1 on every Logon(A).
+    ///   Defaults to false.
+    pub fn init(&mut self, properties: &Properties) -> Result<(), ExtransError> {
+        if let Ok(directory) = properties.get::<String>("fix_store_path") {
+            self.m_store = Box::new(FileStore::new(&directory, &self.m_session_id)?);
+        }
+        self.m_reset_on_logon = properties.get_default::<bool>("fix_reset_on_logon", false);
+        Ok(())
+    }
+
+    pub fn get_session_id(&self) -> &SessionID {
+        &self.m_session_id
+    }
+
+    pub fn get_role(&self) -> SessionRole {
+        self.m_role
+    }
+
+    pub fn get_state(&self) -> SessionState {
+        self.m_state
+    }
+
+    pub fn is_logged_on(&self) -> bool {
+        self.m_state == SessionState::ACTIVE
+    }
+
+    /// Returns the heartbeat interval in seconds.
+    pub fn get_heartbeat_interval(&self) -> i64 {
+        self.m_heartbeat_interval
+    }
+
+    /// Sets how long to wait for the Logon(A) response, or for an acceptor the first Logon.
+    pub fn set_logon_timeout(&mut self, timeout: Duration) {
+        self.m_logon_timeout = timeout;
+    }
+
+    /// Sets how long to wait for the Logout(5) confirmation before disconnecting.
+    pub fn set_logout_timeout(&mut self, timeout: Duration) {
+        self.m_logout_timeout = timeout;
+    }
+
+    /// Sets how many messages received ahead of a gap are held until it is filled, 1000 by
+    /// default. Messages beyond the limit are dropped, the ResendRequest(2) asks for them again.
+    pub fn set_queue_limit(&mut self, limit: usize) {
+        self.m_queue_limit = limit;
+    }
+
+    /// Sets whether both sequence numbers start again at 1 on every Logon(A), with
+    /// ResetSeqNumFlag(141) set on the Logon sent.
+    pub fn set_reset_on_logon(&mut self, reset_on_logon: bool) {
+        self.m_reset_on_logon = reset_on_logon;
+    }
+
+    pub fn get_reset_on_logon(&self) -> bool {
+        self.m_reset_on_logon
+    }
+
+    /// Sets when the session may be logged on. Outside the schedule an acceptor refuses the
+    /// Logon(A) and a logged on session logs out. Without a schedule the session never closes.
+    pub fn set_schedule(&mut self, schedule: Option<SessionSchedule>) {
+        self.m_schedule = schedule;
+    }
+
+    pub fn get_schedule(&self) -> Option<SessionSchedule> {
+        self.m_schedule
+    }
+
+    pub fn is_in_schedule(&self, now: DateTime<Utc>) -> bool {
+        self.m_schedule.is_none_or(|schedule| schedule.is_in_session(now))
+    }
+
+    /// Returns the MsgSeqNum(34) of the next message sent.
+    pub fn get_next_sender_seq_num(&self) -> u64 {
+        self.m_store.get_next_sender_seq_num()
+    }
+
+    /// Returns the MsgSeqNum(34) expected for the next message received.
+    pub fn get_next_target_seq_num(&self) -> u64 {
+        self.m_store.get_next_target_seq_num()
+    }
+
+    /// Store failures do not stop the session: a message that could not be saved is gap filled
+    /// if it is asked for again.
+    fn log_store_error(&self, result: Result<(), ExtransError>) {
+        if let Err(err) = result {
+            log::error!("session {}: {}", self.m_session_id, err);
+        }
+    }
+
+    fn set_next_target_seq_num(&mut self, seq_num: u64) {
+        let result = self.m_store.set_next_target_seq_num(seq_num);
+        self.log_store_error(result);
+    }
+
+    fn reset_store(&mut self) {
+        log::info!("session {}: resetting sequence numbers", self.m_session_id);
+        let result = self.m_store.reset();
+        self.log_store_error(result);
+    }
+
+    fn set_state(&mut self, state: SessionState, now: DateTime<Utc>) {
+        if state != self.m_state {
+            log::debug!("session {}: {} -> {}", self.m_session_id, self.m_state, state);
+            self.m_state = state;
+            self.m_state_changed = now;
+        }
+    }
+
+    /// Returns true while a ResendRequest(2) sent by the session has not been satisfied.
+    pub fn is_resend_pending(&self) -> bool {
+        self.m_resend_end.is_some()
+    }
+
+    fn set_header(&self, message: &mut Ido, seq_num: u64, now: DateTime<Utc>) {
+        message.set_string(&BEGIN_STRING, self.m_session_id.get_begin_string().to_string());
+        message.set_string(&SENDER_COMP_ID, self.m_session_id.get_sender_comp_id().to_string());
+        message.set_string(&TARGET_COMP_ID, self.m_session_id.get_target_comp_id().to_string());
+        message.set_integer(&MSG_SEQ_NUM, seq_num as i64);
+        message.set_datetime(&SENDING_TIME, now);
+    }
+
+    /// Fills in the header of an outbound message and returns the action sending it. Application
+    /// messages are kept for replay.
+    fn send_message(&mut self, msgtype: &str, mut message: Ido, now: DateTime<Utc>) -> SessionAction {
+        let seq_num = self.m_store.get_next_sender_seq_num();
+        self.set_header(&mut message, seq_num, now);
+        let mut result = self.m_store.set_next_sender_seq_num(seq_num + 1);
+        if !is_admin_msgtype(msgtype) {
+            result = result.and_then(|_| self.m_store.save(seq_num, msgtype, &message));
+        }
+        self.log_store_error(result);
+        self.m_last_sent = now;
+        SessionAction::Send { msgtype: msgtype.to_string(), message }
+    }
+
+    /// Sends a stored message again as a possible duplicate, keeping its MsgSeqNum(34).
+    fn resend_message(&mut self, msgtype: &str, mut message: Ido, now: DateTime<Utc>) -> SessionAction {
+        if let Some(sending_time) = message.get_datetime(&SENDING_TIME) {
+            message.set_datetime(&ORIG_SENDING_TIME, sending_time);
+        }
+        message.set_string(&POSS_DUP_FLAG, "Y".to_string());
+        message.set_datetime(&SENDING_TIME, now);
+        self.m_last_sent = now;
+        SessionAction::Send { msgtype: msgtype.to_string(), message }
+    }
+
+    /// Sends a SequenceReset-GapFill(4) numbered `seq_num` skipping to `new_seq_num`.
+    fn gap_fill(&mut self, seq_num: u64, new_seq_num: u64, now: DateTime<Utc>) -> SessionAction {
+        let mut gap_fill = Ido::new();
+        self.set_header(&mut gap_fill, seq_num, now);
+        gap_fill.set_string(&POSS_DUP_FLAG, "Y".to_string());
+        gap_fill.set_datetime(&ORIG_SENDING_TIME, now);
+        gap_fill.set_string(&GAP_FILL_FLAG, "Y".to_string());
+        gap_fill.set_integer(&NEW_SEQ_NO, new_seq_num as i64);
+        self.m_last_sent = now;
+        SessionAction::Send { msgtype: SEQUENCE_RESET.to_string(), message: gap_fill }
+    }
+
+    /// Sends a session level Reject(3) of the inbound message `seq_num`.
+    fn reject(&mut self, seq_num: u64, msgtype: &str, reason: i64, tag: IdoKeyT, text: &str, now: DateTime<Utc>) -> SessionAction {
+        log::warn!("session {}: rejecting message {}: {}", self.m_session_id, seq_num, text);
+        let mut reject = Ido::new();
+        reject.set_integer(&REF_SEQ_NUM, seq_num as i64);
+        reject.set_integer(&REF_TAG_ID, tag as i64);
+        reject.set_string(&REF_MSG_TYPE, msgtype.to_string());
+        reject.set_integer(&SESSION_REJECT_REASON, reason);
+        reject.set_string(&TEXT, text.to_string());
+        self.send_message(REJECT, reject, now)
+    }
+
+    fn logon_message(&self, reset: bool) -> Ido {
+        let mut logon = Ido::new();
+        logon.set_integer(&ENCRYPT_METHOD, 0);
+        logon.set_integer(&HEART_BT_INT, self.m_heartbeat_interval);
+        if reset {
+            logon.set_string(&RESET_SEQ_NUM_FLAG, "Y".to_string());
+        }
+        logon
+    }
+
+    fn logout_message(text: Option<&str>) -> Ido {
+        let mut logout = Ido::new();
+        if let Some(text) = text {
+            logout.set_string(&TEXT, text.to_string());
+        }
+        logout
+    }
+
+    /// Ends the session after a protocol error: Logout(5) is sent if the session is logged on,
+    /// then the connection is closed.
+    fn terminate(&mut self, reason: &str, now: DateTime<Utc>) -> Vec<SessionAction> {
+        log::warn!("session {}: {}", self.m_session_id, reason);
+        let mut actions = Vec::new();
+        if self.m_state == SessionState::ACTIVE {
+            actions.push(self.send_message(LOGOUT, Self::logout_message(Some(reason)), now));
+        }
+        actions.extend(self.disconnect(reason, now));
+        actions
+    }
+
+    fn disconnect(&mut self, reason: &str, now: DateTime<Utc>) -> Vec<SessionAction> {
+        let mut actions = self.on_disconnect(now);
+        actions.push(SessionAction::Disconnect { reason: reason.to_string() });
+        actions
+    }
+
+    /// Notifies the session that the transport connection is up. An initiator sends Logon(A),
+    /// an acceptor waits for the counterparty's Logon.
+    pub fn on_connect(&mut self, now: DateTime<Utc>) -> Vec<SessionAction> {
+        self.m_last_received = now;
+        self.m_test_request = None;
+        match self.m_role {
+            SessionRole::INITIATOR => {
+                self.set_state(SessionState::LOGON_SENT, now);
+                if self.m_reset_on_logon {
+                    self.reset_store();
+                }
+                let logon = self.logon_message(self.m_reset_on_logon);
+                vec![self.send_message(LOGON, logon, now)]
+            },
+            SessionRole::ACCEPTOR => {
+                self.set_state(SessionState::AWAITING_LOGON, now);
+                Vec::new()
+            },
+        }
+    }
+
+    /// Notifies the session that the transport connection was lost.
+    pub fn on_disconnect(&mut self, now: DateTime<Utc>) -> Vec<SessionAction> {
+        let mut actions = Vec::new();
+        if matches!(self.m_state, SessionState::ACTIVE | SessionState::LOGOUT_SENT) {
+            actions.push(SessionAction::LoggedOut);
+        }
+        self.set_state(SessionState::DISCONNECTED, now);
+        self.m_test_request = None;
+        self.m_queue.clear();
+        self.m_resend_end = None;
+        actions
+    }
+
+    /// Processes a decoded inbound message. Admin messages are handled by the session,
+    /// application messages received while logged on, or while a Logout(5) sent awaits its
+    /// confirmation, are returned for delivery.
+    pub fn on_message(&mut self, message: Ido, now: DateTime<Utc>) -> Vec<SessionAction> {
+        if self.m_state == SessionState::DISCONNECTED {
+            log::warn!("session {}: message received while disconnected", self.m_session_id);
+            return Vec::new();
+        }
+        self.m_last_received = now;
+        self.m_test_request = None;
+
+        let msgtype = match message.get_string(&MSG_TYPE) {
+            Some(msgtype) => msgtype,
+            None => return self.terminate("message has no MsgType(35)", now),
+        };
+        if let Err(reason) = self.check_header(&message) {
+            return self.terminate(&reason, now);
+        }
+
+        match self.m_state {
+            SessionState::AWAITING_LOGON | SessionState::LOGON_SENT if msgtype != LOGON => {
+                return self.terminate(&format!("first message is of type '{}' rather than Logon(A)", msgtype), now);
+            },
+            _ => {},
+        }
+
+        let seq_num = match get_integer(&message, MSG_SEQ_NUM) {
+            Some(seq_num) if seq_num > 0 => seq_num as u64,
+            _ => return self.terminate("message has no valid MsgSeqNum(34)", now),
+        };
+
+        if msgtype == LOGON {
+            self.on_logon_reset(&message);
+        }
+
+        let expected = self.get_next_target_seq_num();
+        let mut actions = if msgtype == SEQUENCE_RESET && !get_flag(&message, GAP_FILL_FLAG) {
+            // a SequenceReset-Reset applies whatever its MsgSeqNum
+            self.on_sequence_reset(&message, seq_num, now)
+        } else if seq_num < expected {
+            return self.on_seq_num_too_low(&msgtype, &message, seq_num, now);
+        } else if seq_num > expected {
+            return self.on_seq_num_too_high(msgtype, message, seq_num, now);
+        } else {
+            self.process(msgtype, message, seq_num, now)
+        };
+        actions.extend(self.process_queue(now));
+        actions
+    }
+
+    /// Processes a message whose MsgSeqNum(34) is `seq_num`. The expected MsgSeqNum moves on when
+    /// it is the expected one; Logon(A), Logout(5) and ResendRequest(2) are also processed ahead
+    /// of a gap.
+    fn process(&mut self, msgtype: String, message: Ido, seq_num: u64, now: DateTime<Utc>) -> Vec<SessionAction> {
+        if seq_num == self.get_next_target_seq_num() {
+            self.set_next_target_seq_num(seq_num + 1);
+        }
+        if let Some(actions) = self.check_poss_dup(&msgtype, &message, seq_num, now) {
+            return actions;
+        }
+
+        match msgtype.as_str() {
+            LOGON => self.on_logon(&message, now),
+            RESEND_REQUEST => self.on_resend_request(&message, seq_num, now),
+            SEQUENCE_RESET => self.on_sequence_reset(&message, seq_num, now),
+            HEARTBEAT => Vec::new(),
+            TEST_REQUEST => {
+                let mut heartbeat = Ido::new();
+                if let Some(test_req_id) = message.get_string(&TEST_REQ_ID) {
+                    heartbeat.set_string(&TEST_REQ_ID, test_req_id);
+                }
+                vec![self.send_message(HEARTBEAT, heartbeat, now)]
+            },
+            LOGOUT => self.on_logout(now),
+            _ if matches!(self.m_state, SessionState::ACTIVE | SessionState::LOGOUT_SENT) => vec![SessionAction::Deliver { msgtype, message }],
+            _ => {
+                log::debug!("session {}: ignoring message of type '{}' while {}", self.m_session_id, msgtype, self.m_state);
+                Vec::new()
+            },
+        }
+    }
+
+    /// Processes the queued messages the expected MsgSeqNum(34) has reached, dropping those a
+    /// gap fill skipped.
+    fn process_queue(&mut self, now: DateTime<Utc>) -> Vec<SessionAction> {
+        let mut actions = Vec::new();
+        while matches!(self.m_state, SessionState::ACTIVE | SessionState::LOGOUT_SENT) {
+            let seq_num = self.get_next_target_seq_num();
+            self.m_queue = self.m_queue.split_off(&seq_num);
+            let message = match self.m_queue.remove(&seq_num) {
+                Some(message) => message,
+                None => break,
+            };
+            let msgtype = message.get_string(&MSG_TYPE).unwrap_or_default();
+            actions.extend(self.process(msgtype, message, seq_num, now));
+        }
+        match self.m_resend_end {
+            Some(end) if self.get_next_target_seq_num() > end => {
+                log::info!("session {}: resend complete, next expected MsgSeqNum {}", self.m_session_id, self.get_next_target_seq_num());
+                self.m_resend_end = None;
+            },
+            _ => {},
+        }
+        actions
+    }
+
+    fn on_seq_num_too_low(&mut self, msgtype: &str, message: &Ido, seq_num: u64, now: DateTime<Utc>) -> Vec<SessionAction> {
+        if get_flag(message, POSS_DUP_FLAG) {
+            if let Some(actions) = self.check_poss_dup(msgtype, message, seq_num, now) {
+                return actions;
+            }
+            log::debug!("session {}: ignoring possible duplicate {}", self.m_session_id, seq_num);
+            return Vec::new();
+        }
+        let reason = format!("MsgSeqNum(34) too low, expected {} but received {}", self.get_next_target_seq_num(), seq_num);
+        self.terminate(&reason, now)
+    }
+
+    fn on_seq_num_too_high(&mut self, msgtype: String, message: Ido, seq_num: u64, now: DateTime<Utc>) -> Vec<SessionAction> {
+        let mut actions = Vec::new();
+        match msgtype.as_str() {
+            LOGON | LOGOUT | RESEND_REQUEST => actions = self.process(msgtype, message, seq_num, now),
+            _ if self.m_queue.len() < self.m_queue_limit => {
+                self.m_queue.insert(seq_num, message);
+            },
+            _ => log::debug!("session {}: queue full, dropping {} until it is resent", self.m_session_id, seq_num),
+        }
+        if self.m_state != SessionState::ACTIVE {
+            return actions;
+        }
+        match self.m_resend_end {
+            Some(end) => self.m_resend_end = Some(end.max(seq_num)),
+            None => {
+                log::info!("session {}: MsgSeqNum(34) gap, expected {} but received {}", self.m_session_id, self.get_next_target_seq_num(), seq_num);
+                let mut resend_request = Ido::new();
+                resend_request.set_integer(&BEGIN_SEQ_NO, self.get_next_target_seq_num() as i64);
+                resend_request.set_integer(&END_SEQ_NO, 0);
+                actions.push(self.send_message(RESEND_REQUEST, resend_request, now));
+                self.m_resend_end = Some(seq_num);
+            },
+        }
+        actions
+    }
+
+    /// Checks the OrigSendingTime(122) of a possible duplicate. Returns the actions rejecting the
+    /// message, if it is rejected. A SequenceReset(4) is never checked, gap fills are sent as
+    /// possible duplicates and some counterparties leave out their OrigSendingTime.
+    fn check_poss_dup(&mut self, msgtype: &str, message: &Ido, seq_num: u64, now: DateTime<Utc>) -> Option<Vec<SessionAction>> {
+        if !get_flag(message, POSS_DUP_FLAG) || msgtype == SEQUENCE_RESET {
+            return None;
+        }
+        match (get_timestamp(message, ORIG_SENDING_TIME), get_timestamp(message, SENDING_TIME)) {
+            (None, _) => {
+                let text = "OrigSendingTime(122) is required when PossDupFlag(43) is set";
+                Some(vec![self.reject(seq_num, msgtype, REQUIRED_TAG_MISSING, ORIG_SENDING_TIME, text, now)])
+            },
+            (Some(orig_sending_time), Some(sending_time)) if orig_sending_time > sending_time => {
+                let text = "OrigSendingTime(122) is later than SendingTime(52)";
+                let mut actions = vec![self.reject(seq_num, msgtype, SENDING_TIME_ACCURACY_PROBLEM, ORIG_SENDING_TIME, text, now)];
+                actions.extend(self.terminate(text, now));
+                Some(actions)
+            },
+            _ => None,
+        }
+    }
+
+    /// Handles both forms of SequenceReset(4). A gap fill has already moved the expected MsgSeqNum
+    /// past its own, a reset ignores its MsgSeqNum. Neither may move the expected MsgSeqNum back.
+    fn on_sequence_reset(&mut self, message: &Ido, seq_num: u64, now: DateTime<Utc>) -> Vec<SessionAction> {
+        let new_seq_num = match get_integer(message, NEW_SEQ_NO) {
+            Some(new_seq_num) if new_seq_num > 0 => new_seq_num as u64,
+            _ => return vec![self.reject(seq_num, SEQUENCE_RESET, REQUIRED_TAG_MISSING, NEW_SEQ_NO, "SequenceReset(4) has no valid NewSeqNo(36)", now)],
+        };
+        if new_seq_num < self.get_next_target_seq_num() {
+            let text = format!("NewSeqNo(36) {} is lower than the expected MsgSeqNum {}", new_seq_num, self.get_next_target_seq_num());
+            return vec![self.reject(seq_num, SEQUENCE_RESET, VALUE_IS_INCORRECT, NEW_SEQ_NO, &text, now)];
+        }
+        log::info!("session {}: sequence reset from {} to {}", self.m_session_id, self.get_next_target_seq_num(), new_seq_num);
+        self.set_next_target_seq_num(new_seq_num);
+        Vec::new()
+    }
+
+    /// Replays the messages a ResendRequest(2) asks for. Application messages are resent as
+    /// possible duplicates; runs of admin messages and messages no longer held become a single
+    /// SequenceReset-GapFill(4).
+    fn on_resend_request(&mut self, message: &Ido, seq_num: u64, now: DateTime<Utc>) -> Vec<SessionAction> {
+        let (begin, end) = match (get_integer(message, BEGIN_SEQ_NO), get_integer(message, END_SEQ_NO)) {
+            (Some(begin), Some(end)) if begin > 0 && end >= 0 => (begin as u64, end as u64),
+            _ => return vec![self.reject(seq_num, RESEND_REQUEST, REQUIRED_TAG_MISSING, BEGIN_SEQ_NO, "ResendRequest(2) has no valid range", now)],
+        };
+        let last = self.get_next_sender_seq_num() - 1;
+        let end = if end == 0 || end > last { last } else { end };
+        log::info!("session {}: resending {} to {}", self.m_session_id, begin, end);
+
+        let mut actions = Vec::new();
+        let mut gap_start = None;
+        for resend_seq_num in begin..=end {
+            let stored = self.m_store.get(resend_seq_num).unwrap_or_else(|err| {
+                log::error!("session {}: {}", self.m_session_id, err);
+                None
+            });
+            match stored {
+                Some((msgtype, message)) => {
+                    if let Some(start) = gap_start.take() {
+                        actions.push(self.gap_fill(start, resend_seq_num, now));
+                    }
+                    actions.push(self.resend_message(&msgtype, message, now));
+                },
+                None => {
+                    gap_start.get_or_insert(resend_seq_num);
+                },
+            }
+        }
+        if let Some(start) = gap_start {
+            actions.push(self.gap_fill(start, end + 1, now));
+        }
+        actions
+    }
+
+    /// Checks the BeginString(8) and CompIDs of an inbound message against the session.
+    fn check_header(&self, message: &Ido) -> Result<(), String> {
+        let expected = [
+            (BEGIN_STRING, self.m_session_id.get_begin_string()),
+            (SENDER_COMP_ID, self.m_session_id.get_target_comp_id()),
+            (TARGET_COMP_ID, self.m_session_id.get_sender_comp_id()),
+        ];
+        for (tag, value) in expected {
+            match message.get_string(&tag) {
+                Some(received) if received != value => return Err(format!("tag {} is '{}' rather than '{}'", tag, received, value)),
+                _ => {},
+            }
+        }
+        Ok(())
+    }
+
+    /// Applies a sequence number reset ahead of the MsgSeqNum(34) check of an inbound Logon(A).
+    /// An acceptor resets its store when asked to by ResetSeqNumFlag(141) or configured to; an
+    /// initiator that did not ask for the reset only starts expecting 1 again.
+    fn on_logon_reset(&mut self, message: &Ido) {
+        let requested = get_flag(message, RESET_SEQ_NUM_FLAG);
+        match self.m_state {
+            SessionState::AWAITING_LOGON if requested || self.m_reset_on_logon => self.reset_store(),
+            SessionState::LOGON_SENT if requested && !self.m_reset_on_logon => self.set_next_target_seq_num(1),
+            _ => {},
+        }
+    }
+
+    fn on_logon(&mut self, message: &Ido, now: DateTime<Utc>) -> Vec<SessionAction> {
+        match self.m_state {
+            SessionState::AWAITING_LOGON => {
+                if !self.is_in_schedule(now) {
+                    return self.terminate("Logon(A) outside the session schedule", now);
+                }
+                match get_integer(message, HEART_BT_INT) {
+                    Some(interval) if (0..=MAX_HEARTBEAT_INTERVAL).contains(&interval) => self.m_heartbeat_interval = interval,
+                    _ => return self.terminate("Logon(A) has no valid HeartBtInt(108)", now),
+                }
+                let reset = self.m_reset_on_logon || get_flag(message, RESET_SEQ_NUM_FLAG);
+                let logon = self.logon_message(reset);
+                let response = self.send_message(LOGON, logon, now);
+                self.set_state(SessionState::ACTIVE, now);
+                log::info!("session {}: logged on, heartbeat interval {}s", self.m_session_id, self.m_heartbeat_interval);
+                vec![response, SessionAction::LoggedOn]
+            },
+            SessionState::LOGON_SENT => {
+                self.set_state(SessionState::ACTIVE, now);
+                log::info!("session {}: logged on, heartbeat interval {}s", self.m_session_id, self.m_heartbeat_interval);
+                vec![SessionAction::LoggedOn]
+            },
+            _ => self.terminate("unexpected Logon(A) on a logged on session", now),
+        }
+    }
+
+    fn on_logout(&mut self, now: DateTime<Utc>) -> Vec<SessionAction> {
+        let mut actions = Vec::new();
+        if self.m_state != SessionState::LOGOUT_SENT {
+            actions.push(self.send_message(LOGOUT, Self::logout_message(None), now));
+        }
+        log::info!("session {}: logged out", self.m_session_id);
+        actions.extend(self.disconnect("logout", now));
+        actions
+    }
+
+    /// Returns the application message with the header `send` would give it at `now`, without
+    /// taking its MsgSeqNum(34), so that it can be encoded before it is sent. Fails if the session
+    /// is not logged on.
+    pub fn prepare(&self, message: &Ido, now: DateTime<Utc>) -> Result<Ido, ExtransError> {
+        if self.m_state != SessionState::ACTIVE {
+            return Err(ExtransError::SessionError(format!("session {} is {}, not logged on", self.m_session_id, self.m_state)));
+        }
+        let mut message = message.clone();
+        self.set_header(&mut message, self.m_store.get_next_sender_seq_num(), now);
+        Ok(message)
+    }
+
+    /// Sends an application message. Fails if the session is not logged on.
+    pub fn send(&mut self, msgtype: &str, message: Ido, now: DateTime<Utc>) -> Result<Vec<SessionAction>, ExtransError> {
+        let message = self.prepare(&message, now)?;
+        Ok(vec![self.send_message(msgtype, message, now)])
+    }
+
+    /// Starts a logout. The connection is closed once the counterparty confirms it or the
+    /// logout timeout expires.
+    pub fn logout(&mut self, text: Option<&str>, now: DateTime<Utc>) -> Vec<SessionAction> {
+        match self.m_state {
+            SessionState::ACTIVE => {
+                let logout = self.send_message(LOGOUT, Self::logout_message(text), now);
+                self.set_state(SessionState::LOGOUT_SENT, now);
+                vec![logout]
+            },
+            SessionState::AWAITING_LOGON | SessionState::LOGON_SENT => self.disconnect("logout before logon", now),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Drives the timers: heartbeats, test requests and the logon and logout timeouts. Must be
+    /// called regularly, e.g. every second.
+    pub fn on_timer(&mut self, now: DateTime<Utc>) -> Vec<SessionAction> {
+        match self.m_state {
+            SessionState::AWAITING_LOGON | SessionState::LOGON_SENT if now - self.m_state_changed >= self.m_logon_timeout => {
+                self.terminate("timed out waiting for Logon(A)", now)
+            },
+            SessionState::LOGOUT_SENT if now - self.m_state_changed >= self.m_logout_timeout => {
+                self.disconnect("timed out waiting for Logout(5)", now)
+            },
+            SessionState::ACTIVE if !self.is_in_schedule(now) => self.logout(Some("end of the session schedule"), now),
+            SessionState::ACTIVE if self.m_heartbeat_interval > 0 => self.check_heartbeat(now),
+            _ => Vec::new(),
+        }
+    }
+
+    fn check_heartbeat(&mut self, now: DateTime<Utc>) -> Vec<SessionAction> {
+        // an interval too long to represent never expires
+        let (interval, grace) = match (
+            Duration::try_seconds(self.m_heartbeat_interval),
+            self.m_heartbeat_interval.checked_mul(1200).and_then(Duration::try_milliseconds),
+        ) {
+            (Some(interval), Some(grace)) => (interval, grace),
+            _ => return Vec::new(),
+        };
+
+        let mut actions = Vec::new();
+        match &self.m_test_request {
+            Some((test_req_id, sent)) if now - *sent >= grace => {
+                let reason = format!("no response to TestRequest(1) '{}'", test_req_id);
+                return self.terminate(&reason, now);
+            },
+            Some(_) => {},
+            None if now - self.m_last_received >= grace => {
+                self.m_test_request_counter += 1;
+                let test_req_id = format!("TEST{}", self.m_test_request_counter);
+                let mut test_request = Ido::new();
+                test_request.set_string(&TEST_REQ_ID, test_req_id.clone());
+                actions.push(self.send_message(TEST_REQUEST, test_request, now));
+                self.m_test_request = Some((test_req_id, now));
+            },
+            None => {},
+        }
+
+        if now - self.m_last_sent >= interval {
+            actions.push(self.send_message(HEARTBEAT, Ido::new(), now));
+        }
+        actions
+    }
+}
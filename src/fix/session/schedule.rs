@@ -0,0 +1,71 @@
+use std::fmt;
+
+use chrono::{DateTime, Datelike, NaiveTime, Timelike, Utc, Weekday};
+
+const SECONDS_PER_DAY: u32 = 24 * 60 * 60;
+
+/// When a session may be logged on, in UTC.
+///
+/// A daily schedule runs from the start to the end time every day, a weekly schedule from the
+/// start time on the start day to the end time on the end day. A window whose end comes before
+/// its start wraps around midnight, or the end of the week, and one whose end equals its start
+/// never closes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SessionSchedule {
+    m_start_time: NaiveTime,
+    m_end_time: NaiveTime,
+    m_start_day: Option<Weekday>,
+    m_end_day: Option<Weekday>,
+}
+
+impl SessionSchedule {
+    pub fn daily(start_time: NaiveTime, end_time: NaiveTime) -> Self {
+        SessionSchedule { m_start_time: start_time, m_end_time: end_time, m_start_day: None, m_end_day: None }
+    }
+
+    pub fn weekly(start_day: Weekday, start_time: NaiveTime, end_day: Weekday, end_time: NaiveTime) -> Self {
+        SessionSchedule { m_start_time: start_time, m_end_time: end_time, m_start_day: Some(start_day), m_end_day: Some(end_day) }
+    }
+
+    pub fn get_start_time(&self) -> NaiveTime {
+        self.m_start_time
+    }
+
+    pub fn get_end_time(&self) -> NaiveTime {
+        self.m_end_time
+    }
+
+    pub fn get_start_day(&self) -> Option<Weekday> {
+        self.m_start_day
+    }
+
+    pub fn get_end_day(&self) -> Option<Weekday> {
+        self.m_end_day
+    }
+
+    /// Returns true if `now` falls within the schedule.
+    pub fn is_in_session(&self, now: DateTime<Utc>) -> bool {
+        let time = now.time().num_seconds_from_midnight();
+        let (now, start, end) = match (self.m_start_day, self.m_end_day) {
+            (Some(start_day), Some(end_day)) => {
+                let position = |day: Weekday, time: u32| day.num_days_from_monday() * SECONDS_PER_DAY + time;
+                (position(now.weekday(), time), position(start_day, self.m_start_time.num_seconds_from_midnight()), position(end_day, self.m_end_time.num_seconds_from_midnight()))
+            },
+            _ => (time, self.m_start_time.num_seconds_from_midnight(), self.m_end_time.num_seconds_from_midnight()),
+        };
+        match start.cmp(&end) {
+            std::cmp::Ordering::Less => start <= now && now < end,
+            std::cmp::Ordering::Greater => now >= start || now < end,
+            std::cmp::Ordering::Equal => true,
+        }
+    }
+}
+
+impl fmt::Display for SessionSchedule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (self.m_start_day, self.m_end_day) {
+            (Some(start_day), Some(end_day)) => write!(f, "{} {} to {} {} UTC", start_day, self.m_start_time, end_day, self.m_end_time),
+            _ => write!(f, "{} to {} UTC daily", self.m_start_time, self.m_end_time),
+        }
+    }
+}
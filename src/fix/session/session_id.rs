@@ -0,0 +1,38 @@
+use std::fmt;
+
+/// Identifies a FIX session by its BeginString(8) and the local and remote CompIDs. The
+/// SenderCompID is the one this side puts in SenderCompID(49) of the messages it sends.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SessionID {
+    m_begin_string: String,
+    m_sender_comp_id: String,
+    m_target_comp_id: String,
+}
+
+impl SessionID {
+    pub fn new(begin_string: &str, sender_comp_id: &str, target_comp_id: &str) -> Self {
+        SessionID {
+            m_begin_string: begin_string.to_string(),
+            m_sender_comp_id: sender_comp_id.to_string(),
+            m_target_comp_id: target_comp_id.to_string(),
+        }
+    }
+
+    pub fn get_begin_string(&self) -> &str {
+        &self.m_begin_string
+    }
+
+    pub fn get_sender_comp_id(&self) -> &str {
+        &self.m_sender_comp_id
+    }
+
+    pub fn get_target_comp_id(&self) -> &str {
+        &self.m_target_comp_id
+    }
+}
+
+impl fmt::Display for SessionID {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}->{}", self.m_begin_string, self.m_sender_comp_id, self.m_target_comp_id)
+    }
+}
@@ -0,0 +1,217 @@
+use std::fmt::Display;
+use std::str::FromStr;
+use std::time;
+
+use chrono::{NaiveTime, Weekday};
+
+use crate::{ExtransError, FIX};
+use crate::fix::Strictness;
+use crate::properties::Properties;
+
+use super::{FileStore, MemoryStore, MessageStore, Session, SessionID, SessionRole, SessionSchedule};
+
+/// The configuration of one FIX session, read from properties.
+///
+/// The sessions are listed by name in `fix_sessions`, separated by commas. Each setting is looked
+/// up as `<name>.<property>` first, then as `<property>`, so values shared by all sessions are
+/// given once and overridden per session. Both lookups go through the namespace.class.app
+/// hierarchy of `Properties`.
+///
+/// - `fix_begin_string`, `fix_sender_comp_id`, `fix_target_comp_id`: the session identity.
+/// - `fix_connection_type`: initiator or acceptor.
+/// - `fix_host`, `fix_port`: the address an initiator connects to or an acceptor listens on.
+///   The host defaults to 0.0.0.0 for an acceptor and is required for an initiator.
+/// - `fix_heartbeat_interval`: HeartBtInt(108) in seconds, defaults to 30.
+/// - `fix_reconnect_interval`: seconds an initiator waits before reconnecting, defaults to 30.
+/// - `fix_start_time`, `fix_end_time`: daily schedule as HH:MM:SS UTC, with `fix_start_day`
+///   and `fix_end_day` for a weekly one. Without them the session never closes.
+/// - `fix_dictionary`: dictionary paths, separated by commas, e.g. FIXT11.xml,FIX50SP2.xml.
+/// - `fix_strictness`: strict, warn or ignore, see `Strictness`. Defaults to strict.
+/// - `fix_reset_on_logon`: true to reset the sequence numbers on every Logon(A).
+/// - `fix_store_path`: directory of the `FileStore`, sequence numbers are kept in memory without.
+#[derive(Clone, Debug)]
+pub struct SessionSettings {
+    m_name: String,
+    m_session_id: SessionID,
+    m_role: SessionRole,
+    m_host: String,
+    m_port: u16,
+    m_heartbeat_interval: i64,
+    m_reconnect_interval: time::Duration,
+    m_schedule: Option<SessionSchedule>,
+    m_dictionaries: Vec<String>,
+    m_strictness: Strictness,
+    m_reset_on_logon: bool,
+    m_store_path: Option<String>,
+}
+
+/// Looks up the settings of one named session.
+struct Lookup<'a> {
+    m_properties: &'a Properties,
+    m_name: &'a str,
+}
+
+impl Lookup<'_> {
+    fn get_string(&self, prop: &str) -> Option<String> {
+        self.m_properties.get::<String>(&format!("{}.{}", self.m_name, prop)).or_else(|_| self.m_properties.get::<String>(prop)).ok()
+    }
+
+    fn get<T: FromStr>(&self, prop: &str) -> Result<Option<T>, ExtransError>
+    where
+    T::Err: Display,
+    {
+        match self.get_string(prop) {
+            Some(value) => value.parse::<T>().map(Some)
+                .map_err(|e| ExtransError::ParseError(format!("{} of session {}: '{}', {}", prop, self.m_name, value, e))),
+            None => Ok(None),
+        }
+    }
+
+    fn require<T: FromStr>(&self, prop: &str) -> Result<T, ExtransError>
+    where
+    T::Err: Display,
+    {
+        self.get::<T>(prop)?.ok_or_else(|| ExtransError::PropertyNotFound(format!("{} of session {}", prop, self.m_name)))
+    }
+}
+
+impl SessionSettings {
+    /// Reads the settings of every session listed in `fix_sessions`.
+    pub fn load_all(properties: &Properties) -> Result<Vec<SessionSettings>, ExtransError> {
+        let sessions = properties.get::<String>("fix_sessions")?;
+        sessions.split(',').map(str::trim).filter(|name| !name.is_empty()).map(|name| Self::load(properties, name)).collect()
+    }
+
+    /// Reads the settings of the session `name`.
+    pub fn load(properties: &Properties, name: &str) -> Result<SessionSettings, ExtransError> {
+        let lookup = Lookup { m_properties: properties, m_name: name };
+        let session_id = SessionID::new(
+            &lookup.require::<String>("fix_begin_string")?,
+            &lookup.require::<String>("fix_sender_comp_id")?,
+            &lookup.require::<String>("fix_target_comp_id")?,
+        );
+        let role = lookup.require::<SessionRole>("fix_connection_type")?;
+        let host = match (lookup.get::<String>("fix_host")?, role) {
+            (Some(host), _) => host,
+            (None, SessionRole::ACCEPTOR) => "0.0.0.0".to_string(),
+            (None, SessionRole::INITIATOR) => return Err(ExtransError::PropertyNotFound(format!("fix_host of session {}", name))),
+        };
+
+        Ok(SessionSettings {
+            m_name: name.to_string(),
+            m_session_id: session_id,
+            m_role: role,
+            m_host: host,
+            m_port: lookup.require::<u16>("fix_port")?,
+            m_heartbeat_interval: lookup.get::<i64>("fix_heartbeat_interval")?.unwrap_or(30),
+            m_reconnect_interval: time::Duration::from_secs(lookup.get::<u64>("fix_reconnect_interval")?.unwrap_or(30)),
+            m_schedule: Self::load_schedule(&lookup)?,
+            m_dictionaries: lookup.get_string("fix_dictionary").map(|paths| {
+                paths.split(',').map(str::trim).filter(|path| !path.is_empty()).map(str::to_string).collect()
+            }).unwrap_or_default(),
+            m_strictness: lookup.get::<Strictness>("fix_strictness")?.unwrap_or(Strictness::STRICT),
+            m_reset_on_logon: lookup.get::<bool>("fix_reset_on_logon")?.unwrap_or(false),
+            m_store_path: lookup.get::<String>("fix_store_path")?,
+        })
+    }
+
+    fn load_schedule(lookup: &Lookup) -> Result<Option<SessionSchedule>, ExtransError> {
+        let parse_time = |prop: &str| -> Result<Option<NaiveTime>, ExtransError> {
+            match lookup.get_string(prop) {
+                Some(value) => NaiveTime::parse_from_str(&value, "%H:%M:%S").map(Some)
+                    .map_err(|e| ExtransError::ParseError(format!("{} of session {}: '{}', {}", prop, lookup.m_name, value, e))),
+                None => Ok(None),
+            }
+        };
+        let times = (parse_time("fix_start_time")?, parse_time("fix_end_time")?);
+        let days = (lookup.get::<Weekday>("fix_start_day")?, lookup.get::<Weekday>("fix_end_day")?);
+        match (times, days) {
+            ((None, None), (None, None)) => Ok(None),
+            ((Some(start_time), Some(end_time)), (None, None)) => Ok(Some(SessionSchedule::daily(start_time, end_time))),
+            ((Some(start_time), Some(end_time)), (Some(start_day), Some(end_day))) => {
+                Ok(Some(SessionSchedule::weekly(start_day, start_time, end_day, end_time)))
+            },
+            _ => Err(ExtransError::SetupError(format!(
+                "session {}: a schedule needs both fix_start_time and fix_end_time, and both or neither of fix_start_day and fix_end_day",
+                lookup.m_name))),
+        }
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.m_name
+    }
+
+    pub fn get_session_id(&self) -> &SessionID {
+        &self.m_session_id
+    }
+
+    pub fn get_role(&self) -> SessionRole {
+        self.m_role
+    }
+
+    pub fn get_host(&self) -> &str {
+        &self.m_host
+    }
+
+    pub fn get_port(&self) -> u16 {
+        self.m_port
+    }
+
+    /// Returns the address as host:port.
+    pub fn get_address(&self) -> String {
+        format!("{}:{}", self.m_host, self.m_port)
+    }
+
+    pub fn get_heartbeat_interval(&self) -> i64 {
+        self.m_heartbeat_interval
+    }
+
+    pub fn get_reconnect_interval(&self) -> time::Duration {
+        self.m_reconnect_interval
+    }
+
+    pub fn get_schedule(&self) -> Option<SessionSchedule> {
+        self.m_schedule
+    }
+
+    pub fn get_dictionaries(&self) -> &[String] {
+        &self.m_dictionaries
+    }
+
+    pub fn get_strictness(&self) -> Strictness {
+        self.m_strictness
+    }
+
+    pub fn get_reset_on_logon(&self) -> bool {
+        self.m_reset_on_logon
+    }
+
+    pub fn get_store_path(&self) -> Option<&str> {
+        self.m_store_path.as_deref()
+    }
+
+    /// Creates a codec with the session dictionaries loaded and its strictness set.
+    pub fn create_fix(&self) -> Result<FIX, ExtransError> {
+        if self.m_dictionaries.is_empty() {
+            return Err(ExtransError::PropertyNotFound(format!("fix_dictionary of session {}", self.m_name)));
+        }
+        let mut fix = FIX::new();
+        for dictionary in &self.m_dictionaries {
+            fix.load_dictionary(dictionary)?;
+        }
+        fix.set_strictness(self.m_strictness);
+        Ok(fix)
+    }
+
+    /// Creates the session, opening its file store if a store path is set.
+    pub fn create_session(&self) -> Result<Session, ExtransError> {
+        let store: Box<dyn MessageStore> = match &self.m_store_path {
+            Some(directory) => Box::new(FileStore::new(directory, &self.m_session_id)?),
+            None => Box::new(MemoryStore::new()),
+        };
+        let mut session = Session::with_store(self.m_session_id.clone(), self.m_role, self.m_heartbeat_interval, store);
+        session.set_reset_on_logon(self.m_reset_on_logon);
+        session.set_schedule(self.m_schedule);
+        Ok(session)
+    }
+}
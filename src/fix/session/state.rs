@@ -0,0 +1,59 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// The state of a FIX session.
+///
+/// - `DISCONNECTED`: no transport connection.
+/// - `AWAITING_LOGON`: an acceptor is connected and waits for the counterparty's Logon(A).
+/// - `LOGON_SENT`: an initiator has sent Logon(A) and waits for the response.
+/// - `ACTIVE`: logged on, messages are exchanged and heartbeats monitored.
+/// - `LOGOUT_SENT`: Logout(5) has been sent and the session waits for the confirmation.
+#[allow(non_camel_case_types)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SessionState {
+    DISCONNECTED,
+    AWAITING_LOGON,
+    LOGON_SENT,
+    ACTIVE,
+    LOGOUT_SENT,
+}
+
+impl fmt::Display for SessionState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SessionState::DISCONNECTED => write!(f, "disconnected"),
+            SessionState::AWAITING_LOGON => write!(f, "awaiting logon"),
+            SessionState::LOGON_SENT => write!(f, "logon sent"),
+            SessionState::ACTIVE => write!(f, "active"),
+            SessionState::LOGOUT_SENT => write!(f, "logout sent"),
+        }
+    }
+}
+
+/// Whether the session opens the connection and sends the first Logon(A), or accepts it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SessionRole {
+    INITIATOR,
+    ACCEPTOR,
+}
+
+impl FromStr for SessionRole {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_ascii_lowercase().as_str() {
+            "initiator" => Ok(SessionRole::INITIATOR),
+            "acceptor" => Ok(SessionRole::ACCEPTOR),
+            _ => Err(format!("invalid session role '{}', expected initiator or acceptor", value)),
+        }
+    }
+}
+
+impl fmt::Display for SessionRole {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SessionRole::INITIATOR => write!(f, "initiator"),
+            SessionRole::ACCEPTOR => write!(f, "acceptor"),
+        }
+    }
+}
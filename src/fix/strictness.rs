@@ -0,0 +1,39 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// How the decoder reacts when the CheckSum(10) or BodyLength(9) of a received message does not
+/// match the bytes actually received.
+///
+/// - `STRICT`: the message is rejected with `ExtransError::ChecksumMismatch` or
+///   `ExtransError::BodyLengthMismatch`.
+/// - `WARN`: the mismatch is logged at warn level and the message is accepted.
+/// - `IGNORE`: the values are not verified.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Strictness {
+    STRICT,
+    WARN,
+    IGNORE,
+}
+
+impl FromStr for Strictness {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_ascii_lowercase().as_str() {
+            "strict" => Ok(Strictness::STRICT),
+            "warn" => Ok(Strictness::WARN),
+            "ignore" => Ok(Strictness::IGNORE),
+            _ => Err(format!("invalid strictness '{}', expected strict, warn or ignore", value)),
+        }
+    }
+}
+
+impl fmt::Display for Strictness {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Strictness::STRICT => write!(f, "strict"),
+            Strictness::WARN => write!(f, "warn"),
+            Strictness::IGNORE => write!(f, "ignore"),
+        }
+    }
+}
@@ -0,0 +1,147 @@
+use crate::ido::{Ido, IdoItem, IdoItemType, IdoKeyT};
+
+use super::dictionary::Dictionary;
+use super::encoder::{format_value, BEGIN_STRING, BODY_LENGTH, CHECK_SUM, MSG_TYPE};
+use super::field::{Field, FieldType};
+use super::group::Group;
+use super::violation::Violation;
+
+// generated by the encoder, so neither required from nor checked in the Ido
+const GENERATED_TAGS: [IdoKeyT; 4] = [BEGIN_STRING, BODY_LENGTH, MSG_TYPE, CHECK_SUM];
+
+/// Checks an Ido against the header, message and trailer definitions of the dictionary and
+/// returns every violation found, in a stable order.
+pub(crate) fn validate(dictionary: &Dictionary, ido: &Ido, msgtype: &str) -> Vec<Violation> {
+    let mut violations: Vec<Violation> = Vec::new();
+
+    let message = match dictionary.get_message(msgtype) {
+        Some(message) => message,
+        None => {
+            violations.push(Violation::UnknownMessageType { msgtype: msgtype.to_string() });
+            return violations;
+        }
+    };
+
+    let context = format!("message {}", msgtype);
+    let sections = [dictionary.get_header(), message, dictionary.get_trailer()];
+
+    for section in sections.iter() {
+        for field in section.iter_fields() {
+            let tag = field.get_tag();
+            if section.is_required(tag) && !GENERATED_TAGS.contains(&tag) && !ido.contains(&tag) {
+                violations.push(Violation::MissingRequiredField { context: context.clone(), tag });
+            }
+        }
+    }
+
+    for tag in sorted_tags(ido) {
+        if GENERATED_TAGS.contains(&tag) {
+            continue;
+        }
+
+        let item = ido.get_item_ref(&tag).unwrap();
+        match sections.iter().find(|section| section.has_tag(tag)) {
+            Some(section) => match section.get_group(tag) {
+                Some(group) => validate_group(dictionary, group, item, &context, &mut violations),
+                None => validate_value(dictionary, tag, item, &context, &mut violations),
+            },
+            None => violations.push(Violation::UndefinedTag { context: context.clone(), tag }),
+        }
+    }
+
+    violations
+}
+
+fn sorted_tags(ido: &Ido) -> Vec<IdoKeyT> {
+    let mut tags: Vec<IdoKeyT> = ido.keys().cloned().collect();
+    tags.sort();
+    tags
+}
+
+fn validate_group(dictionary: &Dictionary, group: &Group, item: &IdoItem, context: &str, violations: &mut Vec<Violation>) {
+    let tag = group.get_parent_tag();
+
+    if item.get_type() != IdoItemType::ARRAY {
+        // a count without entries, which is only consistent when the count is zero
+        let field = dictionary.get_field(tag);
+        let value = format_value(tag, item, field).unwrap_or_default();
+        match value.parse::<usize>() {
+            Ok(0) => {},
+            Ok(count) => violations.push(Violation::GroupCountMismatch { context: context.to_string(), tag, count, entries: 0 }),
+            Err(_) => violations.push(Violation::InvalidDataType { context: context.to_string(), tag, field_type: FieldType::NUMINGROUP, value }),
+        }
+        return;
+    }
+
+    // the count a decoded group declared, kept in step with the entries by append_array
+    if item.m_integer != item.m_array.len() as i64 {
+        violations.push(Violation::GroupCountMismatch { context: context.to_string(), tag, count: item.m_integer.max(0) as usize, entries: item.m_array.len() });
+    }
+
+    for (n, entry) in item.m_array.iter().enumerate() {
+        let context = format!("{} group {} entry {}", context, tag, n + 1);
+
+        if !entry.contains(&group.get_first_tag()) {
+            violations.push(Violation::MissingDelimiter { context: context.clone(), tag, delimiter: group.get_first_tag() });
+        }
+
+        for field in group.iter_fields() {
+            if group.is_required(field.get_tag()) && !entry.contains(&field.get_tag()) {
+                violations.push(Violation::MissingRequiredField { context: context.clone(), tag: field.get_tag() });
+            }
+        }
+
+        for entry_tag in sorted_tags(entry) {
+            let entry_item = entry.get_item_ref(&entry_tag).unwrap();
+            if !group.has_tag(entry_tag) {
+                violations.push(Violation::UndefinedTag { context: context.clone(), tag: entry_tag });
+            } else if let Some(nested) = group.get_group(entry_tag) {
+                validate_group(dictionary, nested, entry_item, &context, violations);
+            } else {
+                validate_value(dictionary, entry_tag, entry_item, &context, violations);
+            }
+        }
+    }
+}
+
+/// Checks a value is well formed for the field type and, for enumerated fields, allowed. The
+/// value is checked as the encoder would write it.
+fn validate_value(dictionary: &Dictionary, tag: IdoKeyT, item: &IdoItem, context: &str, violations: &mut Vec<Violation>) {
+    let field: &Field = match dictionary.get_field(tag) {
+        Some(field) => field,
+        None => return,
+    };
+    let field_type = field.get_field_type();
+
+    let invalid = |value: String| Violation::InvalidDataType { context: context.to_string(), tag, field_type: field_type.clone(), value };
+    let value = match item.get_type() {
+        IdoItemType::ARRAY => {
+            violations.push(invalid("<array>".to_string()));
+            return;
+        },
+        IdoItemType::BYTES if field_type.is_data() => return,
+        IdoItemType::BYTES => match String::from_utf8(item.m_bytes.clone()) {
+            Ok(value) => value,
+            Err(_) => {
+                violations.push(invalid(String::from_utf8_lossy(&item.m_bytes).into_owned()));
+                return;
+            }
+        },
+        _ => format_value(tag, item, Some(field)).unwrap_or_default(),
+    };
+
+    if !field_type.is_valid(&value) {
+        violations.push(invalid(value));
+        return;
+    }
+
+    let multiple = matches!(field_type, FieldType::MULTIPLECHARVALUE | FieldType::MULTIPLEVALUESTRING | FieldType::MULTIPLESTRINGVALUE);
+    let allowed = if multiple {
+        value.split(' ').all(|v| field.is_valid_value(v))
+    } else {
+        field.is_valid_value(&value)
+    };
+    if !allowed {
+        violations.push(Violation::InvalidEnumValue { context: context.to_string(), tag, value });
+    }
+}
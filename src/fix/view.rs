@@ -0,0 +1,103 @@
+use chrono::{DateTime, Utc};
+
+use crate::ExtransError;
+use crate::ido::{Ido, IdoKeyT};
+
+use super::decoder::{decode_fields, tokenize};
+use super::dictionary::Dictionary;
+use super::encoder::MSG_TYPE;
+use super::field::FieldType;
+
+/// A read only view over a raw FIX tag=value message that borrows the message bytes instead of
+/// copying them.
+///
+/// The tag offsets are indexed in a single pass when the view is created, values are only
+/// parsed when a getter asks for them. Getters return the first occurrence of a tag, so tags
+/// repeated within groups should be read through `iter` or after conversion with `to_ido`.
+/// The typed getters use the dictionary `FieldType` of the tag and return None when the tag
+/// is absent, of another type or holds a malformed value.
+pub struct FixMessageView<'a> {
+    m_dictionary: &'a Dictionary,
+    m_fields: Vec<(IdoKeyT, &'a [u8])>,
+}
+
+impl<'a> FixMessageView<'a> {
+    pub fn new(dictionary: &'a Dictionary, data: &'a [u8]) -> Result<Self, ExtransError> {
+        Ok(FixMessageView {
+            m_dictionary: dictionary,
+            m_fields: tokenize(dictionary, data)?,
+        })
+    }
+
+    /// Returns the MsgType(35) value.
+    pub fn get_msg_type(&self) -> Option<&'a str> {
+        self.get_str(MSG_TYPE)
+    }
+
+    /// Returns the number of fields in the message, group fields included.
+    pub fn len(&self) -> usize {
+        self.m_fields.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.m_fields.is_empty()
+    }
+
+    pub fn contains(&self, tag: IdoKeyT) -> bool {
+        self.m_fields.iter().any(|(t, _)| *t == tag)
+    }
+
+    /// Iterates the (tag, raw value) pairs in message order.
+    pub fn iter(&self) -> impl Iterator<Item = (IdoKeyT, &'a [u8])> + '_ {
+        self.m_fields.iter().cloned()
+    }
+
+    /// Returns the raw value bytes of a tag.
+    pub fn get_bytes(&self, tag: IdoKeyT) -> Option<&'a [u8]> {
+        self.m_fields.iter().find(|(t, _)| *t == tag).map(|(_, value)| *value)
+    }
+
+    /// Returns the value of a tag as text, whatever its type.
+    pub fn get_str(&self, tag: IdoKeyT) -> Option<&'a str> {
+        self.get_bytes(tag).and_then(|value| std::str::from_utf8(value).ok())
+    }
+
+    fn get_typed(&self, tag: IdoKeyT, is_type: fn(&FieldType) -> bool) -> Option<(&FieldType, &'a str)> {
+        let field_type = self.m_dictionary.get_field(tag)?.get_field_type();
+        if !is_type(field_type) {
+            return None;
+        }
+        let value = self.get_str(tag)?;
+        if field_type.is_valid(value) { Some((field_type, value)) } else { None }
+    }
+
+    /// Returns the value of an integer typed tag, e.g. INT, SEQNUM or NUMINGROUP.
+    pub fn get_i64(&self, tag: IdoKeyT) -> Option<i64> {
+        self.get_typed(tag, FieldType::is_integer).and_then(|(_, value)| value.parse().ok())
+    }
+
+    /// Returns the value of a floating-point typed tag, e.g. PRICE, QTY or AMT.
+    pub fn get_f64(&self, tag: IdoKeyT) -> Option<f64> {
+        self.get_typed(tag, FieldType::is_float).and_then(|(_, value)| value.parse().ok())
+    }
+
+    /// Returns the value of a date and time typed tag, e.g. UTCTIMESTAMP or UTCDATEONLY.
+    pub fn get_datetime(&self, tag: IdoKeyT) -> Option<DateTime<Utc>> {
+        self.get_typed(tag, FieldType::is_datetime).and_then(|(field_type, value)| field_type.parse_datetime(value))
+    }
+
+    /// Returns the value of a BOOLEAN tag.
+    pub fn get_bool(&self, tag: IdoKeyT) -> Option<bool> {
+        self.get_typed(tag, |t| *t == FieldType::BOOLEAN).map(|(_, value)| value == "Y")
+    }
+
+    /// Returns the value of a CHAR tag.
+    pub fn get_char(&self, tag: IdoKeyT) -> Option<char> {
+        self.get_typed(tag, |t| *t == FieldType::CHAR).and_then(|(_, value)| value.chars().next())
+    }
+
+    /// Decodes the message into an owned Ido, as `Extrans::decode` would.
+    pub fn to_ido(&self) -> Result<Ido, ExtransError> {
+        decode_fields(self.m_dictionary, &self.m_fields)
+    }
+}
@@ -0,0 +1,41 @@
+use std::fmt;
+
+use crate::ido::IdoKeyT;
+
+use super::field::FieldType;
+
+/// A way in which a message does not conform to the dictionary, as reported by `FIX::validate`.
+///
+/// `context` locates the problem, e.g. "message D" or "message D group 453 entry 2" for the second
+/// entry of the NoPartyIDs(453) group. Group entries are numbered from 1.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Violation {
+    /// The message type is not defined in the dictionary, nothing else is checked.
+    UnknownMessageType { msgtype: String },
+    /// A field the dictionary marks as required is not present.
+    MissingRequiredField { context: String, tag: IdoKeyT },
+    /// A tag is not defined for the message type, or for the group entry.
+    UndefinedTag { context: String, tag: IdoKeyT },
+    /// A value is not one of the enumerated values of the field.
+    InvalidEnumValue { context: String, tag: IdoKeyT, value: String },
+    /// A value is not a well formed value of the field type.
+    InvalidDataType { context: String, tag: IdoKeyT, field_type: FieldType, value: String },
+    /// A group count does not match the number of group entries present.
+    GroupCountMismatch { context: String, tag: IdoKeyT, count: usize, entries: usize },
+    /// A group entry does not hold the delimiter field every entry must start with.
+    MissingDelimiter { context: String, tag: IdoKeyT, delimiter: IdoKeyT },
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Violation::UnknownMessageType { msgtype } => write!(f, "message type '{}' is not defined", msgtype),
+            Violation::MissingRequiredField { context, tag } => write!(f, "{}: required tag {} is missing", context, tag),
+            Violation::UndefinedTag { context, tag } => write!(f, "{}: tag {} is not defined", context, tag),
+            Violation::InvalidEnumValue { context, tag, value } => write!(f, "{}: tag {} value '{}' is not an allowed value", context, tag, value),
+            Violation::InvalidDataType { context, tag, field_type, value } => write!(f, "{}: tag {} value '{}' is not a valid {}", context, tag, value, field_type),
+            Violation::GroupCountMismatch { context, tag, count, entries } => write!(f, "{}: group {} count is {} but {} entries are present", context, tag, count, entries),
+            Violation::MissingDelimiter { context, tag, delimiter } => write!(f, "{}: group {} entry does not start with delimiter tag {}", context, tag, delimiter),
+        }
+    }
+}
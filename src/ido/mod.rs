@@ -1,6 +1,6 @@
 use chrono::{DateTime, Utc};
 use std::collections::HashMap;
-use std::collections::hash_map::IntoIter;
+use std::collections::hash_map::{IntoIter, Keys};
 use std::fmt::Write;
 
 pub type IdoKeyT = i32;
@@ -17,10 +17,12 @@ pub struct IdoItem
     pub m_integer: i64,
     pub m_float: f64,
     pub m_datetime: DateTime<Utc>,
+    pub m_bytes: Vec<u8>,
     pub m_array: IdoArray
 }
 
 impl IdoItem {
+    #[allow(clippy::new_without_default)]
     pub fn new() -> Self {
         IdoItem { 
             m_key: (0),
@@ -30,6 +32,7 @@ impl IdoItem {
             m_integer: (0),
             m_float: (0.0),
             m_datetime: (DateTime::<Utc>::MIN_UTC),
+            m_bytes: (Vec::new()),
             m_array: (IdoArray::new())
         }
     }
@@ -51,6 +54,7 @@ impl IdoItem {
             IdoItemType::FLOAT => Some(self.m_float.to_string()),
             IdoItemType::INTEGER => Some(self.m_integer.to_string()),
             IdoItemType::DATETIME => Some(self.m_datetime.format("%Y-%m-%d %H:%M:%S%.4f").to_string()),
+            IdoItemType::BYTES => Some(String::from_utf8_lossy(&self.m_bytes).into_owned()),
             IdoItemType::ARRAY => Some(format!("<array of {}>", self.m_array.len()))
         }
     }
@@ -63,6 +67,7 @@ pub enum IdoItemType
     INTEGER,
     FLOAT,
     DATETIME,
+    BYTES,
     ARRAY
 }
 
@@ -84,7 +89,7 @@ impl Iterator for OrderedIdoIterator<'_> {
     fn next(&mut self) -> Option<Self::Item> {
         match self.m_ido.m_ordered.get(&(self.m_curr as u64)) {
             Some(key) => {
-                let item = self.m_ido.get_item(&key).unwrap();
+                let item = self.m_ido.get_item(key).unwrap();
                 self.m_curr += 1;
                 Some((*key, item))
             }
@@ -104,7 +109,9 @@ impl IntoIterator for Ido {
     }
 }
 
+#[allow(clippy::needless_return)]
 impl Ido {
+    #[allow(clippy::new_without_default)]
     pub fn new() -> Self {
         Ido {
             m_items: ItemMap::new(),
@@ -133,7 +140,7 @@ impl Ido {
     ///         None => println!("Key: {}, Val: None", key),
     ///     }
     /// }
-    pub fn into_ordered_iterator(&self) -> OrderedIdoIterator {
+    pub fn into_ordered_iterator(&self) -> OrderedIdoIterator<'_> {
         OrderedIdoIterator { m_ido: self, m_curr: 0 }
     }
 
@@ -364,6 +371,54 @@ impl Ido {
         self.set_item(key, item);
     }
 
+    /// Sets a UTC date and time value associated with the given key.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key associated with the value.
+    /// * `val` - The UTC date and time value to be set.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use chrono::Utc;
+    /// use extrans::ido::{Ido};
+    /// let mut ido = Ido::new();
+    /// ido.set_datetime(&52, Utc::now());
+    /// ```
+    pub fn set_datetime (&mut self, key: &IdoKeyT, val: DateTime<Utc>)
+    {
+        let mut item: IdoItem = IdoItem::new();
+        item.m_type = IdoItemType::DATETIME;
+        item.m_datetime = val;
+
+        self.set_item(key, item);
+    }
+
+    /// Sets a raw byte value associated with the given key. Bytes are kept as they are, which
+    /// allows binary payloads such as FIX DATA fields to be held without conversion.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key associated with the value.
+    /// * `val` - The bytes to be set.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use extrans::ido::{Ido};
+    /// let mut ido = Ido::new();
+    /// ido.set_bytes(&96, b"raw\x01data".to_vec());
+    /// ```
+    pub fn set_bytes (&mut self, key: &IdoKeyT, val: Vec<u8>)
+    {
+        let mut item: IdoItem = IdoItem::new();
+        item.m_type = IdoItemType::BYTES;
+        item.m_bytes = val;
+
+        self.set_item(key, item);
+    }
+
     /// Retrieves an item from the collection using the specified key.
     ///
     /// If an item is found in the collection associated with the provided key, a clone of the item is returned within a `Some` variant.
@@ -402,6 +457,86 @@ impl Ido {
         }
     }
 
+    /// Retrieves a reference to the item associated with the given key.
+    ///
+    /// Unlike `get_item` the item is not cloned, which makes this the preferred accessor
+    /// when walking large or nested (array) items.
+    ///
+    /// # Arguments
+    ///
+    /// * `key`: A reference to a key of type `IdoKeyT` used for item lookup.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use extrans::ido::{Ido, IdoItemType};
+    ///
+    /// let mut ido = Ido::new();
+    /// ido.set_integer(&1, 100);
+    ///
+    /// if let Some(item) = ido.get_item_ref(&1) {
+    ///     assert_eq!(item.get_type(), IdoItemType::INTEGER);
+    /// }
+    /// ```
+    pub fn get_item_ref(&self, key: &IdoKeyT) -> Option<&IdoItem> {
+        self.m_items.get(key)
+    }
+
+    /// Returns an iterator over the keys held by the Ido object, in no particular order.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use extrans::ido::Ido;
+    ///
+    /// let mut ido = Ido::new();
+    /// ido.set_string(&1, "value1".to_string());
+    /// ido.set_string(&2, "value2".to_string());
+    ///
+    /// let mut keys: Vec<i32> = ido.keys().cloned().collect();
+    /// keys.sort();
+    /// assert_eq!(keys, vec![1, 2]);
+    /// ```
+    pub fn keys(&self) -> Keys<'_, IdoKeyT, IdoItem> {
+        self.m_items.keys()
+    }
+
+    /// Retrieves the array of Ido objects associated with the given key.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key (integer) associated with the array.
+    ///
+    /// # Returns
+    ///
+    /// Returns an `Option` containing a reference to the array if it exists and is of the correct type, or `None` otherwise.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use extrans::ido::Ido;
+    ///
+    /// let mut ido = Ido::new();
+    /// let mut entry = Ido::new();
+    /// entry.set_string(&448, "TRADER1".to_string());
+    /// ido.append_array(&453, entry);
+    ///
+    /// if let Some(entries) = ido.get_array(&453) {
+    ///     assert_eq!(entries.len(), 1);
+    /// }
+    /// ```
+    pub fn get_array(&self, key: &IdoKeyT) -> Option<&Vec<Ido>> {
+        if let Some(value) = self.m_items.get(key) {
+            if value.m_type != IdoItemType::ARRAY {
+                return None;
+            } else {
+                Some(&value.m_array)
+            }
+        } else {
+            None
+        }
+    }
+
     /// Retrieves a string value associated with the given key.
     ///
     /// # Arguments
@@ -733,6 +868,69 @@ impl Ido {
         }
     }
 
+    /// Retrieves a UTC date and time value associated with the given key.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key (integer) associated with the value.
+    ///
+    /// # Returns
+    ///
+    /// Returns an `Option` containing the date and time value if it exists and is of the correct type, or `None` otherwise.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use extrans::ido::{Ido};
+    /// let ido = Ido::new();
+    /// if let Some(value) = ido.get_datetime(&52) {
+    ///     println!("Value: {}", value);
+    /// } else {
+    ///     println!("Value not found or not a date and time.");
+    /// }
+    /// ```
+    pub fn get_datetime(&self, key: &IdoKeyT) -> Option<DateTime<Utc>> {
+        if let Some(value) = self.m_items.get(key) {
+            if value.m_type != IdoItemType::DATETIME {
+                return None;
+            } else {
+                Some(value.m_datetime)
+            }
+        } else {
+            None
+        }
+    }
+
+    /// Retrieves a raw byte value associated with the given key.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key (integer) associated with the value.
+    ///
+    /// # Returns
+    ///
+    /// Returns an `Option` containing a reference to the bytes if they exist and are of the correct type, or `None` otherwise.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use extrans::ido::{Ido};
+    /// let mut ido = Ido::new();
+    /// ido.set_bytes(&96, vec![1, 2, 3]);
+    /// assert_eq!(ido.get_bytes(&96), Some(&[1u8, 2, 3][..]));
+    /// ```
+    pub fn get_bytes(&self, key: &IdoKeyT) -> Option<&[u8]> {
+        if let Some(value) = self.m_items.get(key) {
+            if value.m_type != IdoItemType::BYTES {
+                return None;
+            } else {
+                Some(&value.m_bytes)
+            }
+        } else {
+            None
+        }
+    }
+
     /// Empties the array associated with the given key, or creates a new empty array if the key doesn't exist.
     ///
     /// # Arguments
@@ -750,7 +948,8 @@ impl Ido {
     ///
     /// If the specified key does not exist or the value associated with the key is not an array,
     /// a new array is created and assigned to the key.
-    /// The provided Ido object is then appended to the array.
+    /// The provided Ido object is then appended to the array, whose integer value is kept as the
+    /// number of entries.
     ///
     /// # Arguments
     ///
@@ -782,8 +981,9 @@ impl Ido {
         }
 
         if let Some(value) = self.m_items.get_mut(key) {
-            (*value).m_type = IdoItemType::ARRAY;
-            (*value).m_array.push(data);
+            value.m_type = IdoItemType::ARRAY;
+            value.m_array.push(data);
+            value.m_integer = value.m_array.len() as i64;
         }
     }
 
@@ -831,6 +1031,7 @@ impl Ido {
     ///
     /// let result = ido.to_string();
     /// ```
+    #[allow(clippy::inherent_to_string)]
     pub fn to_string(&self) -> String {
         let mut result = String::new();
         let mut count = 0;
@@ -7,6 +7,9 @@ pub mod log_builder;
 pub use fix::FIX;
 pub use extrans_error::ExtransError;
 
+use ido::Ido;
+
 pub trait Extrans {
-    fn encode(&self) -> String;
-}
\ No newline at end of file
+    fn encode(&self, ido: &Ido, msgtype: &str) -> Result<Vec<u8>, ExtransError>;
+    fn decode(&self, data: &[u8]) -> Result<Ido, ExtransError>;
+}
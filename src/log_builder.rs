@@ -9,8 +9,11 @@ use log4rs::{
     encode::pattern::PatternEncoder, filter::threshold::ThresholdFilter,
 };
 
+#[allow(clippy::needless_return)]
 fn str_to_level(level: &str, default: log::LevelFilter) -> log::LevelFilter {
-    if level == "debug" {
+    if level == "trace" {
+        return log::LevelFilter::Trace;
+    } else if level == "debug" {
         return log::LevelFilter::Debug;
     } else if level == "info" {
         return log::LevelFilter::Info;
@@ -32,6 +35,7 @@ pub struct LogBuilder {
 }
 
 impl LogBuilder {
+    #[allow(clippy::new_without_default)]
     pub fn new() -> Self {
         LogBuilder {
             m_console_level: log::LevelFilter::Info,
@@ -115,18 +119,19 @@ impl LogBuilder {
         }
 
         // Root logger with console and optional file/rolling loggers
-        // Note: root logger must be at Debug level to catch all
+        // Note: root logger must be at the most verbose appender level to catch all
+        let root_level = std::cmp::max(self.m_console_level, self.m_file_level);
         let root_logger = if !self.m_filename.is_empty() {
             if self.m_filesize_limit > 0 {
                 Root::builder()
                     .appender("console")
                     .appender("rolling_file")
-                    .build(log::LevelFilter::Debug)
+                    .build(root_level)
             } else {
                 Root::builder()
                     .appender("console")
                     .appender("file")
-                    .build(log::LevelFilter::Debug)
+                    .build(root_level)
             }
         } else {
             Root::builder()
@@ -30,6 +30,7 @@ impl PropertiesBuilder {
     /// use extrans::properties::PropertiesBuilder; 
     /// let builder = PropertiesBuilder::new();
     /// ```
+    #[allow(clippy::new_without_default)]
     pub fn new() -> Self {
         PropertiesBuilder {
             m_file_path: None,
@@ -110,6 +111,7 @@ impl PropertiesBuilder {
     /// use extrans::properties::PropertiesBuilder; 
     /// let properties = PropertiesBuilder::new().with_file("config/path").build();
     /// ```
+    #[allow(clippy::useless_format)]
     pub fn build(self) -> Result<Properties, config::ConfigError> {
         let mut properties = Properties::new(self.m_namespace, self.m_class, self.m_app);
         if let Some(path) = self.m_file_path {
@@ -174,6 +176,7 @@ impl Properties {
         value.trim_matches(|c| c == '"' || c == '\'').parse::<T>().map_err(|e| ExtransError::ParseError(e.to_string()))
     }
 
+    #[allow(clippy::match_result_ok, clippy::needless_borrow)]
     fn get_property<T: FromStr>(&self, prop: &str) -> Result<T, ExtransError> 
     where
     T::Err: Display,
@@ -0,0 +1,178 @@
+use extrans::FIX;
+use extrans::ido::Ido;
+use extrans::fix::session::{Acceptor, Application, Initiator, Session, SessionHandle, SessionID, SessionRole};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const DICTIONARY: &str = "tests/config/FIX44.xml";
+
+fn setup_fix() -> Arc<FIX> {
+    let mut fix = FIX::new();
+    fix.load_dictionary(DICTIONARY).unwrap();
+    Arc::new(fix)
+}
+
+/// Records the events of its sessions. When `echo` is set every NewOrderSingle(D) is answered
+/// with an ExecutionReport(8) carrying the same ClOrdID(11).
+struct Recorder {
+    m_echo: bool,
+    m_events: Mutex<Vec<String>>,
+}
+
+impl Recorder {
+    fn new(echo: bool) -> Arc<Self> {
+        Arc::new(Recorder { m_echo: echo, m_events: Mutex::new(Vec::new()) })
+    }
+
+    fn events(&self) -> Vec<String> {
+        self.m_events.lock().unwrap().clone()
+    }
+
+    fn record(&self, session: &SessionHandle, event: String) {
+        self.m_events.lock().unwrap().push(format!("{} {}", session.get_session_id().get_target_comp_id(), event));
+    }
+}
+
+impl Application for Recorder {
+    fn on_logon(&self, session: &SessionHandle) {
+        self.record(session, "logon".to_string());
+    }
+
+    fn on_logout(&self, session: &SessionHandle) {
+        self.record(session, "logout".to_string());
+    }
+
+    fn on_message(&self, session: &SessionHandle, msgtype: &str, message: &Ido) {
+        let cl_ord_id = message.get_string(&11).unwrap_or_default();
+        self.record(session, format!("{} {}", msgtype, cl_ord_id));
+        if self.m_echo && msgtype == "D" {
+            let mut report = Ido::new();
+            report.set_string(&11, cl_ord_id);
+            session.send("8", report).unwrap();
+        }
+    }
+}
+
+fn wait_for(condition: impl Fn() -> bool) -> bool {
+    let until = Instant::now() + Duration::from_secs(5);
+    while Instant::now() < until {
+        if condition() {
+            return true;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    false
+}
+
+fn start_acceptor(fix: &Arc<FIX>, application: Arc<Recorder>, clients: &[&str]) -> Acceptor {
+    let mut acceptor = Acceptor::new(fix.clone(), "127.0.0.1:0", application);
+    for client in clients {
+        acceptor.add_session(Session::new(SessionID::new("FIX.4.4", "BROKER", client), SessionRole::ACCEPTOR, 30)).unwrap();
+    }
+    acceptor.start().unwrap();
+    acceptor
+}
+
+fn start_initiator(fix: &Arc<FIX>, application: Arc<Recorder>, client: &str, acceptor: &Acceptor) -> Initiator {
+    let session = Session::new(SessionID::new("FIX.4.4", client, "BROKER"), SessionRole::INITIATOR, 30);
+    let address = acceptor.get_local_addr().unwrap().to_string();
+    let mut initiator = Initiator::new(fix.clone(), session, &address, application).unwrap();
+    initiator.set_reconnect_interval(Duration::from_millis(100));
+    initiator.start().unwrap();
+    initiator
+}
+
+#[test]
+fn test_connector_sessions_over_loopback() {
+    let fix = setup_fix();
+    let broker = Recorder::new(true);
+    let mut acceptor = start_acceptor(&fix, broker.clone(), &["CLIENT1", "CLIENT2"]);
+    let (client1, client2) = (Recorder::new(false), Recorder::new(false));
+    let mut initiator1 = start_initiator(&fix, client1.clone(), "CLIENT1", &acceptor);
+    let mut initiator2 = start_initiator(&fix, client2.clone(), "CLIENT2", &acceptor);
+    assert!(wait_for(|| initiator1.get_session().is_logged_on() && initiator2.get_session().is_logged_on()));
+
+    // each order is received by the broker session of its client and answered on it
+    let mut order = Ido::new();
+    order.set_string(&11, "ORDER1".to_string());
+    initiator1.get_session().send("D", order.clone()).unwrap();
+    order.set_string(&11, "ORDER2".to_string());
+    initiator2.get_session().send("D", order).unwrap();
+    assert!(wait_for(|| client1.events().len() == 2 && client2.events().len() == 2));
+    assert_eq!(client1.events(), vec!["BROKER logon", "BROKER 8 ORDER1"]);
+    assert_eq!(client2.events(), vec!["BROKER logon", "BROKER 8 ORDER2"]);
+    let events = broker.events();
+    assert!(events.contains(&"CLIENT1 D ORDER1".to_string()));
+    assert!(events.contains(&"CLIENT2 D ORDER2".to_string()));
+    let broker_session = acceptor.get_session(&SessionID::new("FIX.4.4", "BROKER", "CLIENT1")).unwrap();
+    assert_eq!(broker_session.get_next_target_seq_num(), 3);
+    assert_eq!(broker_session.get_next_sender_seq_num(), 3);
+
+    // stopping an initiator logs its session out, the other carries on
+    initiator1.stop();
+    assert_eq!(client1.events().last(), Some(&"BROKER logout".to_string()));
+    assert!(!initiator1.get_session().is_connected());
+    assert!(wait_for(|| !broker_session.is_connected()));
+    assert!(broker.events().contains(&"CLIENT1 logout".to_string()));
+    assert!(initiator2.get_session().is_logged_on());
+
+    acceptor.stop();
+    assert!(wait_for(|| !initiator2.get_session().is_logged_on()));
+    assert!(client2.events().contains(&"BROKER logout".to_string()));
+    initiator2.stop();
+}
+
+#[test]
+fn test_connector_refuses_unencodable_message() {
+    let fix = setup_fix();
+    let broker = Recorder::new(true);
+    let mut acceptor = start_acceptor(&fix, broker.clone(), &["CLIENT1"]);
+    let client = Recorder::new(false);
+    let mut initiator = start_initiator(&fix, client.clone(), "CLIENT1", &acceptor);
+    assert!(wait_for(|| initiator.get_session().is_logged_on()));
+
+    // the refused message takes no MsgSeqNum(34), so the next one leaves no gap to resend
+    let session = initiator.get_session();
+    assert_eq!(session.get_next_sender_seq_num(), 2);
+    assert!(session.send("ZZ", Ido::new()).is_err());
+    assert_eq!(session.get_next_sender_seq_num(), 2);
+    let mut order = Ido::new();
+    order.set_string(&11, "ORDER1".to_string());
+    session.send("D", order).unwrap();
+    assert!(wait_for(|| client.events().len() == 2));
+    assert_eq!(client.events(), vec!["BROKER logon", "BROKER 8 ORDER1"]);
+    assert_eq!(broker.events(), vec!["CLIENT1 logon", "CLIENT1 D ORDER1"]);
+    assert_eq!(acceptor.get_session(&SessionID::new("FIX.4.4", "BROKER", "CLIENT1")).unwrap().get_next_target_seq_num(), 3);
+    assert!(session.is_logged_on());
+
+    initiator.stop();
+    acceptor.stop();
+}
+
+#[test]
+fn test_acceptor_refuses_unknown_and_duplicate_sessions() {
+    let fix = setup_fix();
+    let broker = Recorder::new(false);
+    let acceptor = start_acceptor(&fix, broker.clone(), &["CLIENT1"]);
+
+    let stranger = Recorder::new(false);
+    let initiator = start_initiator(&fix, stranger.clone(), "STRANGER", &acceptor);
+    thread::sleep(Duration::from_millis(300));
+    assert!(!initiator.get_session().is_logged_on());
+    assert!(stranger.events().is_empty());
+    drop(initiator);
+
+    // a second connection for a session already connected is closed
+    let client = Recorder::new(false);
+    let first = start_initiator(&fix, client.clone(), "CLIENT1", &acceptor);
+    assert!(wait_for(|| first.get_session().is_logged_on()));
+    let second = start_initiator(&fix, Recorder::new(false), "CLIENT1", &acceptor);
+    thread::sleep(Duration::from_millis(300));
+    assert!(!second.get_session().is_logged_on());
+    assert!(first.get_session().is_logged_on());
+    assert_eq!(broker.events(), vec!["CLIENT1 logon"]);
+
+    let mut bad_role = Acceptor::new(fix.clone(), "127.0.0.1:0", broker);
+    assert!(bad_role.add_session(Session::new(SessionID::new("FIX.4.4", "BROKER", "CLIENT1"), SessionRole::INITIATOR, 30)).is_err());
+}
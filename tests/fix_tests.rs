@@ -1,13 +1,113 @@
 use extrans::{FIX, Extrans};
+use extrans::ido::Ido;
+use extrans::fix::{Dictionary, DictionaryIssue, FieldType, FixFramer, Strictness, Violation};
+use chrono::{TimeZone, Utc};
+
+const DICTIONARY: &str = "tests/config/FIX44.xml";
+
+fn setup_fix() -> FIX {
+    let mut fix = FIX::new();
+    fix.load_dictionary(DICTIONARY).unwrap();
+    fix
+}
+
+fn to_wire(message: &str) -> Vec<u8> {
+    message.replace('|', "\x01").into_bytes()
+}
+
+fn to_text(message: &[u8]) -> String {
+    String::from_utf8_lossy(message).replace('\x01', "|")
+}
+
+/// Frames a '|' delimited message body with a FIX.4.4 BeginString, BodyLength and CheckSum.
+fn to_message(body: &str) -> Vec<u8> {
+    let body = to_wire(body);
+    let mut message = format!("8=FIX.4.4\x019={}\x01", body.len()).into_bytes();
+    message.extend_from_slice(&body);
+    let checksum = message.iter().map(|b| *b as u32).sum::<u32>() % 256;
+    message.extend_from_slice(format!("10={:03}\x01", checksum).as_bytes());
+    message
+}
 
 #[test]
 fn test_encode() {
-    // Create an instance of your implementation.
-    let fix = FIX::new();
-    let result = fix.encode();
-    let expected = "Hello".to_string();
+    let fix = setup_fix();
+
+    let mut ido = Ido::new();
+    ido.set_string(&112, "TEST".to_string());
+    ido.set_integer(&34, 1);
+    ido.set_string(&52, "20240101-12:00:00.000".to_string());
+    ido.set_string(&56, "TARGET".to_string());
+    ido.set_string(&49, "SENDER".to_string());
 
-    assert_eq!(result, expected);
+    let result = fix.encode(&ido, "0").unwrap();
+    let expected = to_wire("8=FIX.4.4|9=64|35=0|49=SENDER|56=TARGET|34=1|52=20240101-12:00:00.000|112=TEST|10=077|");
+
+    assert_eq!(to_text(&result), to_text(&expected));
+}
+
+#[test]
+fn test_encode_body_length_and_checksum() {
+    let fix = setup_fix();
+
+    let mut ido = Ido::new();
+    ido.set_string(&49, "SENDER".to_string());
+    ido.set_string(&56, "TARGET".to_string());
+    ido.set_integer(&34, 7);
+    ido.set_string(&55, "EURUSD".to_string());
+
+    let mut bid = Ido::new();
+    bid.set_string(&269, "0".to_string());
+    bid.set_f64(&270, 1.1);
+    bid.set_integer(&271, 1000000);
+    ido.append_array(&268, bid);
+
+    let mut offer = Ido::new();
+    offer.set_string(&269, "1".to_string());
+    offer.set_f64(&270, 1.2);
+    ido.append_array(&268, offer);
+
+    let result = to_text(&fix.encode(&ido, "W").unwrap());
+    assert!(result.contains("|268=2|269=0|270=1.1|271=1000000|269=1|270=1.2|"), "{}", result);
+
+    let checksum_start = result.rfind("10=").unwrap();
+    let body_start = result.find("35=").unwrap();
+    assert_eq!(result[..checksum_start].bytes().map(|b| if b == b'|' { 1u32 } else { b as u32 }).sum::<u32>() % 256,
+        result[checksum_start + 3..checksum_start + 6].parse::<u32>().unwrap());
+    assert!(result.starts_with(&format!("8=FIX.4.4|9={}|", checksum_start - body_start)));
+}
+
+#[test]
+fn test_encode_unknown_message_type() {
+    let fix = setup_fix();
+    let result = fix.encode(&Ido::new(), "ZZ");
+    assert!(result.is_err(), "Expected an error, but success returned");
+}
+
+#[test]
+fn test_encode_invalid_values() {
+    let fix = setup_fix();
+    let mut ido = Ido::new();
+    ido.set_string(&55, "x\x0135=A".to_string());
+    assert!(fix.encode(&ido, "D").is_err());
+    let mut ido = Ido::new();
+    ido.set_bytes(&55, b"x\x01".to_vec());
+    assert!(fix.encode(&ido, "D").is_err());
+
+    let mut ido = Ido::new();
+    ido.set_f64(&44, f64::NAN);
+    assert!(fix.encode(&ido, "D").is_err());
+    ido.set_f64(&44, f64::INFINITY);
+    assert!(fix.encode(&ido, "D").is_err());
+    ido.set_f64(&44, 1.5);
+    assert!(fix.encode(&ido, "D").is_ok());
+}
+
+#[test]
+fn test_encode_without_dictionary() {
+    let fix = FIX::new();
+    let result = fix.encode(&Ido::new(), "0");
+    assert!(result.is_err(), "Expected an error, but success returned");
 }
 
 #[test]
@@ -15,4 +115,1075 @@ fn test_load_dictionary_invalid_file() {
     let mut fix = FIX::new();
     let result = fix.load_dictionary("invalid_file.xml");
     assert!(result.is_err(), "Expected an error, but success returned");
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_decode() {
+    let fix = setup_fix();
+    let message = to_wire("8=FIX.4.4|9=64|35=0|49=SENDER|56=TARGET|34=1|52=20240101-12:00:00.000|112=TEST|10=077|");
+
+    let ido = fix.decode(&message).unwrap();
+
+    assert_eq!(ido.get_string(&8), Some("FIX.4.4".to_string()));
+    assert_eq!(ido.get_i64(&9), Some(64));
+    assert_eq!(ido.get_string(&35), Some("0".to_string()));
+    assert_eq!(ido.get_i64(&34), Some(1));
+    assert_eq!(ido.get_string(&112), Some("TEST".to_string()));
+    assert_eq!(ido.get_datetime(&52).unwrap().format("%Y%m%d-%H:%M:%S%.3f").to_string(), "20240101-12:00:00.000");
+}
+
+#[test]
+fn test_decode_typed_values_and_groups() {
+    let fix = setup_fix();
+    let message = to_message("35=W|49=SENDER|56=TARGET|34=2|55=EURUSD|268=2|269=0|270=1.1|271=1000000|269=1|270=1.2|");
+
+    let ido = fix.decode(&message).unwrap();
+    let entries = ido.get_array(&268).unwrap();
+
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].get_string(&269), Some("0".to_string()));
+    assert_eq!(entries[0].get_f64(&270), Some(1.1));
+    assert_eq!(entries[0].get_f64(&271), Some(1000000.0));
+    assert_eq!(entries[1].get_f64(&270), Some(1.2));
+    assert!(!entries[1].contains(&271));
+}
+
+#[test]
+fn test_encode_decode_round_trip() {
+    let fix = setup_fix();
+
+    let mut ido = Ido::new();
+    ido.set_string(&49, "SENDER".to_string());
+    ido.set_string(&56, "TARGET".to_string());
+    ido.set_integer(&34, 3);
+    ido.set_string(&55, "EURUSD".to_string());
+    let mut bid = Ido::new();
+    bid.set_string(&269, "0".to_string());
+    bid.set_f64(&270, 1.25);
+    ido.append_array(&268, bid);
+
+    let decoded = fix.decode(&fix.encode(&ido, "W").unwrap()).unwrap();
+
+    assert_eq!(decoded.get_string(&35), Some("W".to_string()));
+    assert_eq!(decoded.get_i64(&34), Some(3));
+    assert_eq!(decoded.get_string(&55), Some("EURUSD".to_string()));
+    assert_eq!(decoded.get_array(&268).unwrap()[0].get_f64(&270), Some(1.25));
+}
+
+#[test]
+fn test_encode_decode_empty_group() {
+    let fix = setup_fix();
+    let message = to_message("35=D|49=SENDER|56=TARGET|34=2|453=0|");
+
+    let decoded = fix.decode(&message).unwrap();
+    assert_eq!(decoded.get_array(&453).map(|entries| entries.len()), Some(0));
+    let encoded = fix.encode(&decoded, "D").unwrap();
+    assert!(to_text(&encoded).contains("|453=0|"), "{}", to_text(&encoded));
+    assert_eq!(fix.decode(&encoded).unwrap().get_array(&453).map(|entries| entries.len()), Some(0));
+}
+
+#[test]
+fn test_decode_group_without_delimiter() {
+    let fix = setup_fix();
+    let message = to_message("35=W|55=EURUSD|268=1|270=1.1|269=0|");
+    assert!(fix.decode(&message).is_err(), "Expected an error, but success returned");
+}
+
+#[test]
+fn test_decode_malformed_field() {
+    let fix = setup_fix();
+    assert!(fix.decode(&to_message("35=0|abc|")).is_err());
+    assert!(fix.decode(&to_message("35=0|34=x|")).is_err());
+
+    let mut fix = setup_fix();
+    fix.set_strictness(Strictness::IGNORE);
+    assert!(fix.decode(&to_wire("8=FIX.4.4|9=5|35=0|10=000")).is_err());
+}
+
+#[test]
+fn test_dictionary_queries() {
+    let fix = setup_fix();
+    let dictionary = fix.get_dictionary().unwrap();
+
+    let field = dictionary.get_field(54).unwrap();
+    assert_eq!(field.get_name(), "Side");
+    assert_eq!(dictionary.get_field_by_name(&"OrdType".to_string()).unwrap().get_tag(), 40);
+    assert!(dictionary.get_field(99999).is_none());
+
+    let message = dictionary.get_message("D").unwrap();
+    assert_eq!(message.get_name(), "NewOrderSingle");
+    assert_eq!(message.get_category(), "app");
+    let tags: Vec<i32> = message.iter_fields().map(|f| f.get_tag()).collect();
+    assert!(tags.contains(&54));
+    assert!(tags.contains(&55), "component fields are expanded into the message");
+    assert!(message.is_required(11));
+    assert!(!message.is_required(1));
+
+    let message = dictionary.get_message("W").unwrap();
+    let group = message.get_group(268).unwrap();
+    assert!(message.is_required(268));
+    assert_eq!(group.get_first_tag(), 269);
+    assert!(group.is_required(269));
+    assert!(!group.is_required(270));
+    assert_eq!(message.iter_groups().count(), 1);
+}
+
+#[test]
+fn test_dictionary_not_loaded() {
+    let fix = FIX::new();
+    assert!(fix.get_dictionary().is_none());
+}
+
+#[test]
+fn test_encode_declared_field_order() {
+    let fix = setup_fix();
+
+    let mut ido = Ido::new();
+    ido.set_f64(&44, 101.5);
+    ido.set_string(&40, "2".to_string());
+    ido.set_f64(&38, 100.0);
+    ido.set_string(&60, "20240101-12:00:00.000".to_string());
+    ido.set_string(&54, "1".to_string());
+    ido.set_string(&55, "VOD.L".to_string());
+    ido.set_string(&1, "ACC1".to_string());
+    ido.set_string(&11, "ORDER1".to_string());
+
+    let result = to_text(&fix.encode(&ido, "D").unwrap());
+    assert!(result.contains("|35=D|11=ORDER1|1=ACC1|55=VOD.L|54=1|60=20240101-12:00:00.000|38=100|40=2|44=101.5|10="), "{}", result);
+}
+
+#[test]
+fn test_dictionary_required_flags() {
+    let fix = setup_fix();
+    let dictionary = fix.get_dictionary().unwrap();
+    let message = dictionary.get_message("D").unwrap();
+
+    assert_eq!(&message.get_tags()[..4], &[11, 453, 1, 55]);
+    assert!(message.is_required(55), "required field of a required component");
+    assert!(!message.is_required(453), "group of an optional component");
+    assert!(!message.is_required(38), "optional field of a required component");
+    assert!(message.is_required(40));
+    assert!(!message.is_required(44));
+}
+
+#[test]
+fn test_dictionary_header_and_trailer() {
+    let fix = setup_fix();
+    let dictionary = fix.get_dictionary().unwrap();
+
+    assert_eq!(&dictionary.get_header().get_tags()[..6], &[8, 9, 35, 49, 56, 34]);
+    assert!(dictionary.get_header().is_required(52));
+    assert!(dictionary.get_header().get_group(627).is_some());
+    assert_eq!(dictionary.get_trailer().get_tags(), vec![93, 89, 10]);
+    assert!(dictionary.is_header_field(49));
+    assert!(!dictionary.is_header_field(55));
+    assert!(dictionary.is_trailer_field(10));
+}
+
+#[test]
+fn test_encode_header_and_trailer_placement() {
+    let fix = setup_fix();
+
+    let mut ido = Ido::new();
+    ido.set_string(&89, "SIG".to_string());
+    ido.set_integer(&93, 3);
+    ido.set_string(&112, "TEST".to_string());
+    ido.set_string(&52, "20240101-12:00:00.000".to_string());
+    ido.set_integer(&34, 9);
+    let mut hop = Ido::new();
+    hop.set_string(&628, "HOP1".to_string());
+    hop.set_integer(&630, 4);
+    ido.append_array(&627, hop);
+    ido.set_string(&56, "TARGET".to_string());
+    ido.set_string(&49, "SENDER".to_string());
+
+    let result = to_text(&fix.encode(&ido, "0").unwrap());
+    assert!(result.contains("|35=0|49=SENDER|56=TARGET|34=9|52=20240101-12:00:00.000|627=1|628=HOP1|630=4|112=TEST|93=3|89=SIG|10="), "{}", result);
+
+    let decoded = fix.decode(&to_wire(&result)).unwrap();
+    assert_eq!(decoded.get_array(&627).unwrap()[0].get_string(&628), Some("HOP1".to_string()));
+}
+
+#[test]
+fn test_dictionary_field_values() {
+    let fix = setup_fix();
+    let dictionary = fix.get_dictionary().unwrap();
+
+    let side = dictionary.get_field(54).unwrap();
+    assert!(side.has_values());
+    assert!(side.is_valid_value("1"));
+    assert!(!side.is_valid_value("9"));
+    assert_eq!(side.get_value_description("2"), Some("SELL"));
+    assert_eq!(side.get_value_description("9"), None);
+    assert_eq!(side.iter_values().map(|(v, _)| v.as_str()).collect::<Vec<&str>>(), vec!["1", "2", "5"]);
+
+    let symbol = dictionary.get_field(55).unwrap();
+    assert!(!symbol.has_values());
+    assert!(symbol.is_valid_value("ANYTHING"));
+}
+
+#[test]
+fn test_dictionary_nested_groups() {
+    let fix = setup_fix();
+    let dictionary = fix.get_dictionary().unwrap();
+
+    let legs = dictionary.get_message("AB").unwrap().get_group(555).unwrap();
+    assert_eq!(legs.get_first_tag(), 600, "first field of the InstrumentLeg component");
+    let allocs = legs.get_group(670).unwrap();
+    assert_eq!(allocs.get_first_tag(), 671);
+    assert!(legs.has_tag(670));
+    assert!(!legs.has_tag(671));
+
+    let entries = dictionary.get_message("W").unwrap().get_group(268).unwrap();
+    assert!(entries.has_tag(453), "component inside a message level group is expanded");
+    assert_eq!(entries.get_group(453).unwrap().get_first_tag(), 448);
+}
+
+#[test]
+fn test_encode_decode_nested_groups() {
+    let fix = setup_fix();
+
+    let mut ido = Ido::new();
+    ido.set_string(&11, "ML1".to_string());
+    ido.set_string(&54, "1".to_string());
+    ido.set_string(&40, "2".to_string());
+    ido.set_string(&60, "20240101-12:00:00.000".to_string());
+    for (side, accounts) in [("1", vec!["A1", "A2"]), ("2", vec!["B1"])] {
+        let mut leg = Ido::new();
+        leg.set_string(&624, side.to_string());
+        leg.set_string(&600, format!("LEG{}", side));
+        leg.set_f64(&687, 10.0);
+        for account in accounts {
+            let mut alloc = Ido::new();
+            alloc.set_string(&671, account.to_string());
+            alloc.set_f64(&673, 5.0);
+            leg.append_array(&670, alloc);
+        }
+        ido.append_array(&555, leg);
+    }
+
+    let result = to_text(&fix.encode(&ido, "AB").unwrap());
+    assert!(result.contains("|555=2|600=LEG1|687=10|624=1|670=2|671=A1|673=5|671=A2|673=5|600=LEG2|687=10|624=2|670=1|671=B1|673=5|60="), "{}", result);
+
+    let decoded = fix.decode(&to_wire(&result)).unwrap();
+    let legs = decoded.get_array(&555).unwrap();
+    assert_eq!(legs.len(), 2);
+    assert_eq!(legs[0].get_array(&670).unwrap().len(), 2);
+    assert_eq!(legs[0].get_array(&670).unwrap()[1].get_string(&671), Some("A2".to_string()));
+    assert_eq!(legs[1].get_string(&624), Some("2".to_string()));
+    assert_eq!(legs[1].get_array(&670).unwrap()[0].get_f64(&673), Some(5.0));
+    assert_eq!(decoded.get_string(&40), Some("2".to_string()));
+}
+
+#[test]
+fn test_dictionary_components_inside_groups() {
+    let fix = setup_fix();
+    let dictionary = fix.get_dictionary().unwrap();
+
+    let legs = dictionary.get_message("AB").unwrap().get_group(555).unwrap();
+    assert!(legs.has_tag(602));
+    assert!(legs.get_group(539).is_some(), "NestedParties group inside the NoLegs group");
+
+    let parties = dictionary.get_message("D").unwrap().get_group(453).unwrap();
+    let sub_ids = parties.get_group(802).unwrap();
+    assert_eq!(sub_ids.get_first_tag(), 523);
+    assert!(sub_ids.has_tag(803));
+
+    let parties = dictionary.get_message("W").unwrap().get_group(268).unwrap().get_group(453).unwrap();
+    assert!(parties.get_group(802).is_some());
+}
+
+#[test]
+fn test_encode_decode_components_inside_groups() {
+    let fix = setup_fix();
+
+    let mut sub_id = Ido::new();
+    sub_id.set_string(&523, "DESK1".to_string());
+    sub_id.set_integer(&803, 2);
+    let mut party = Ido::new();
+    party.set_string(&448, "TRADER1".to_string());
+    party.set_integer(&452, 11);
+    party.append_array(&802, sub_id);
+    let mut leg = Ido::new();
+    leg.set_string(&600, "LEG1".to_string());
+    leg.set_f64(&687, 10.0);
+
+    let mut ido = Ido::new();
+    ido.set_string(&11, "ML1".to_string());
+    ido.append_array(&453, party);
+    ido.set_string(&54, "1".to_string());
+    ido.append_array(&555, leg);
+    ido.set_string(&40, "1".to_string());
+
+    let result = to_text(&fix.encode(&ido, "AB").unwrap());
+    assert!(result.contains("|11=ML1|453=1|448=TRADER1|452=11|802=1|523=DESK1|803=2|54=1|555=1|600=LEG1|687=10|40=1|"), "{}", result);
+
+    let decoded = fix.decode(&to_wire(&result)).unwrap();
+    let party = &decoded.get_array(&453).unwrap()[0];
+    assert_eq!(party.get_array(&802).unwrap()[0].get_string(&523), Some("DESK1".to_string()));
+    assert_eq!(decoded.get_array(&555).unwrap()[0].get_string(&600), Some("LEG1".to_string()));
+}
+
+#[test]
+fn test_load_dictionary_cyclic_components() {
+    let mut fix = FIX::new();
+    match fix.load_dictionary("tests/config/FIX44_cyclic.xml") {
+        Err(extrans::ExtransError::SetupError(msg)) => assert!(msg.contains("Parties -> PtysSubGrp -> Parties"), "{}", msg),
+        _ => panic!("Expected a setup error for the cyclic component reference"),
+    }
+}
+
+#[test]
+fn test_validate_dictionary() {
+    let fix = setup_fix();
+    let issues = fix.get_dictionary().unwrap().validate();
+    assert!(issues.is_empty(), "{:?}", issues);
+}
+
+#[test]
+fn test_validate_broken_dictionary() {
+    let mut dictionary = Dictionary::new();
+    dictionary.load("tests/config/FIX44_broken.xml").unwrap();
+    let issues = dictionary.validate();
+
+    let expected = vec![
+        DictionaryIssue::DuplicateTag { tag: 11, name: "ClientOrderID".to_string(), existing: "ClOrdID".to_string() },
+        DictionaryIssue::DuplicateName { name: "PartyID".to_string(), tag: 449, existing: 448 },
+        DictionaryIssue::InvalidAttribute { context: "field BadNumber".to_string(), attribute: "number".to_string(), value: "X1".to_string() },
+        DictionaryIssue::MissingAttribute { context: "field NoType".to_string(), element: "field".to_string(), attribute: "type".to_string() },
+        DictionaryIssue::UnpairedDataField { name: "Payload".to_string() },
+        DictionaryIssue::MissingAttribute { context: "component Parties group NoPartyIDs".to_string(), element: "field".to_string(), attribute: "name".to_string() },
+        DictionaryIssue::MissingAttribute { context: "message Heartbeat".to_string(), element: "message".to_string(), attribute: "msgtype".to_string() },
+        DictionaryIssue::UnknownField { context: "message NewOrderSingle".to_string(), name: "ClOrdID".to_string() },
+        DictionaryIssue::UnknownField { context: "message NewOrderSingle".to_string(), name: "Account".to_string() },
+        DictionaryIssue::UndefinedComponent { context: "message NewOrderSingle".to_string(), name: "Instrument".to_string() },
+        DictionaryIssue::InvalidGroupCountType { context: "message NewOrderSingle".to_string(), name: "NoAllocs".to_string(), field_type: "INT".to_string() },
+    ];
+    for issue in expected.iter() {
+        assert!(issues.contains(issue), "missing {} in {:?}", issue, issues);
+    }
+    assert_eq!(issues.len(), expected.len(), "{:?}", issues);
+
+    // the dictionary is still usable, the later definitions win
+    assert_eq!(dictionary.get_field(11).unwrap().get_name(), "ClientOrderID");
+    assert_eq!(dictionary.get_field_by_name(&"PartyID".to_string()).unwrap().get_tag(), 449);
+    assert!(dictionary.get_field(448).is_none());
+    assert!(dictionary.get_message("D").is_some());
+}
+
+#[test]
+fn test_field_types() {
+    for name in ["INT", "LENGTH", "NUMINGROUP", "SEQNUM", "CHAR", "BOOLEAN", "MULTIPLEVALUESTRING", "CURRENCY",
+                 "EXCHANGE", "COUNTRY", "DATA", "XMLDATA", "TZTIMESTAMP", "UTCDATEONLY", "MONTHYEAR", "PERCENTAGE"] {
+        assert_eq!(FieldType::from_name(name).to_string(), name);
+    }
+    assert_eq!(FieldType::from_name("UNKNOWN"), FieldType::STRING);
+
+    let dictionary = setup_fix();
+    let dictionary = dictionary.get_dictionary().unwrap();
+    assert_eq!(*dictionary.get_field(54).unwrap().get_field_type(), FieldType::CHAR);
+    assert_eq!(*dictionary.get_field(43).unwrap().get_field_type(), FieldType::BOOLEAN);
+    assert_eq!(*dictionary.get_field(9).unwrap().get_field_type(), FieldType::LENGTH);
+    assert_eq!(dictionary.get_field(96).unwrap().to_string(), "field: 96 RawData DATA");
+}
+
+#[test]
+fn test_field_type_values() {
+    assert!(FieldType::CHAR.is_valid("1"));
+    assert!(!FieldType::CHAR.is_valid("12"));
+    assert!(FieldType::BOOLEAN.is_valid("Y"));
+    assert!(!FieldType::BOOLEAN.is_valid("true"));
+    assert!(FieldType::MULTIPLECHARVALUE.is_valid("A B C"));
+    assert!(!FieldType::MULTIPLECHARVALUE.is_valid("AB C"));
+    assert!(FieldType::CURRENCY.is_valid("USD"));
+    assert!(!FieldType::CURRENCY.is_valid("US"));
+    assert!(!FieldType::SEQNUM.is_valid("-1"));
+    assert!(!FieldType::SEQNUM.is_valid("9223372036854775808"));
+    assert!(!FieldType::LENGTH.is_valid("18446744073709551615"));
+    assert!(FieldType::PRICE.is_valid("-1.25"));
+    assert!(FieldType::PRICE.is_valid("100."));
+    assert!(!FieldType::PRICE.is_valid("NaN"));
+    assert!(!FieldType::PRICE.is_valid("inf"));
+    assert!(!FieldType::PRICE.is_valid("1e5"));
+    assert!(!FieldType::PRICE.is_valid("."));
+    assert!(!FieldType::DAYOFMONTH.is_valid("32"));
+    assert!(FieldType::MONTHYEAR.is_valid("202403"));
+    assert!(FieldType::MONTHYEAR.is_valid("202403w2"));
+    assert!(!FieldType::MONTHYEAR.is_valid("202413"));
+    assert!(!FieldType::MONTHYEAR.is_valid("2024é1"));
+    assert!(FieldType::UTCTIMEONLY.is_valid("12:30:00.250"));
+    assert!(FieldType::TZTIMEONLY.is_valid("07:39Z"));
+
+    let expected = Utc.with_ymd_and_hms(2024, 3, 1, 12, 30, 0).unwrap();
+    assert_eq!(FieldType::TZTIMESTAMP.parse_datetime("20240301-07:30:00-05"), Some(expected));
+    assert_eq!(FieldType::TZTIMESTAMP.parse_datetime("20240301-14:30+02:00"), Some(expected));
+    assert_eq!(FieldType::UTCTIMESTAMP.parse_datetime("20240301-12:30:00"), Some(expected));
+    assert_eq!(FieldType::UTCDATEONLY.format_datetime(&expected), "20240301");
+    assert_eq!(FieldType::UTCTIMEONLY.format_datetime(&expected), "12:30:00.000");
+    assert_eq!(FieldType::TZTIMESTAMP.format_datetime(&expected), "20240301-12:30:00.000Z");
+}
+
+#[test]
+fn test_decode_encode_field_types() {
+    let fix = setup_fix();
+
+    let ido = fix.decode(&to_message("35=W|49=SENDER|56=TARGET|34=2|43=Y|55=EURUSD|268=1|269=0|272=20240301|273=12:30:00|")).unwrap();
+    assert_eq!(ido.get_string(&43), Some("Y".to_string()));
+    let entry = &ido.get_array(&268).unwrap()[0];
+    assert_eq!(entry.get_datetime(&272), Some(Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap()));
+    assert_eq!(entry.get_string(&273), Some("12:30:00".to_string()));
+
+    assert!(fix.decode(&to_message("35=W|49=SENDER|56=TARGET|34=2|43=X|")).is_err());
+    assert!(fix.decode(&to_message("35=D|49=SENDER|56=TARGET|34=2|54=12|")).is_err());
+    assert!(fix.decode(&to_message("35=D|49=SENDER|56=TARGET|34=9223372036854775808|")).is_err());
+    assert!(fix.decode(&to_message("35=D|49=SENDER|56=TARGET|34=2|44=NaN|")).is_err());
+    assert!(fix.decode(&to_message("35=D|49=SENDER|56=TARGET|34=2|200=2024é1|")).is_err());
+
+    let mut ido = Ido::new();
+    ido.set_integer(&43, 1);
+    let mut entry = Ido::new();
+    entry.set_string(&269, "0".to_string());
+    entry.set_datetime(&272, Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap());
+    ido.append_array(&268, entry);
+    let result = to_text(&fix.encode(&ido, "W").unwrap());
+    assert!(result.contains("|43=Y|"), "{}", result);
+    assert!(result.contains("|269=0|272=20240301|"), "{}", result);
+}
+
+#[test]
+fn test_data_fields_linked_to_length() {
+    let fix = setup_fix();
+    let dictionary = fix.get_dictionary().unwrap();
+
+    for (length, data) in [(93, 89), (95, 96), (212, 213), (354, 355)] {
+        assert_eq!(dictionary.get_field(data).unwrap().get_length_tag(), Some(length));
+        assert_eq!(dictionary.get_field(length).unwrap().get_data_tag(), Some(data));
+    }
+    assert_eq!(dictionary.get_field(9).unwrap().get_data_tag(), None);
+    assert_eq!(dictionary.get_message("A").unwrap().get_field(96).unwrap().get_length_tag(), Some(95));
+}
+
+#[test]
+fn test_encode_decode_data_fields() {
+    let fix = setup_fix();
+    let payload = b"<xml a=\"1\">\x01binary\x01</xml>".to_vec();
+
+    let mut ido = Ido::new();
+    ido.set_integer(&98, 0);
+    ido.set_integer(&108, 30);
+    ido.set_integer(&95, 999);
+    ido.set_bytes(&96, payload.clone());
+    ido.set_string(&213, "<a>1=2</a>".to_string());
+
+    let encoded = fix.encode(&ido, "A").unwrap();
+    let expected_data = [b"95=25\x0196=".as_slice(), &payload, b"\x01"].concat();
+    assert!(encoded.windows(expected_data.len()).any(|w| w == expected_data.as_slice()), "{}", to_text(&encoded));
+    assert!(to_text(&encoded).contains("|212=10|213=<a>1=2</a>|"), "{}", to_text(&encoded));
+
+    let decoded = fix.decode(&encoded).unwrap();
+    assert_eq!(decoded.get_i64(&95), Some(25));
+    assert_eq!(decoded.get_bytes(&96), Some(payload.as_slice()));
+    assert_eq!(decoded.get_bytes(&213), Some(b"<a>1=2</a>".as_slice()));
+    assert_eq!(decoded.get_i64(&108), Some(30));
+}
+
+#[test]
+fn test_decode_data_field_wrong_length() {
+    let mut fix = setup_fix();
+    fix.set_strictness(Strictness::IGNORE);
+    let mut message = to_wire("8=FIX.4.4|9=5|35=A|98=0|108=30|95=4|96=");
+    message.extend_from_slice(b"ab\x01cdef\x0110=000\x01");
+    assert!(fix.decode(&message).is_err());
+
+    // a length beyond the end of the message, or beyond any offset
+    let message = to_wire("8=FIX.4.4|9=5|35=A|98=0|108=30|95=1000|96=abc|10=000|");
+    assert!(fix.decode(&message).is_err());
+    let message = to_wire("8=FIX.4.4|9=5|35=A|98=0|108=30|95=18446744073709551615|96=abc|10=000|");
+    assert!(fix.decode(&message).is_err());
+}
+
+#[test]
+fn test_message_view() {
+    let fix = setup_fix();
+    let message = to_message("35=8|49=SENDER|56=TARGET|34=12|43=Y|52=20240101-12:00:00.500|37=O1|17=E1|150=F|39=2|55=VOD.L|54=1|32=100|31=101.5|");
+
+    let view = fix.view(&message).unwrap();
+    assert_eq!(view.get_msg_type(), Some("8"));
+    assert_eq!(view.len(), 17);
+    assert_eq!(view.get_str(55).unwrap(), "VOD.L");
+    assert_eq!(view.get_i64(34), Some(12));
+    assert_eq!(view.get_f64(31), Some(101.5));
+    assert_eq!(view.get_f64(34), None, "SEQNUM is not a floating-point type");
+    assert_eq!(view.get_char(150), Some('F'));
+    assert_eq!(view.get_bool(43), Some(true));
+    assert_eq!(view.get_datetime(52), Some(Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap() + chrono::Duration::milliseconds(500)));
+    assert!(view.get_str(11).is_none());
+    assert!(!view.contains(11));
+
+    let ido = view.to_ido().unwrap();
+    assert_eq!(ido.get_string(&37), Some("O1".to_string()));
+    assert_eq!(ido.get_f64(&32), Some(100.0));
+}
+
+#[test]
+fn test_message_view_groups_and_data() {
+    let fix = setup_fix();
+    let mut ido = Ido::new();
+    ido.set_integer(&98, 0);
+    ido.set_integer(&108, 30);
+    ido.set_bytes(&96, b"a\x01b".to_vec());
+    let encoded = fix.encode(&ido, "A").unwrap();
+
+    let view = fix.view(&encoded).unwrap();
+    assert_eq!(view.get_bytes(96), Some(b"a\x01b".as_slice()));
+    assert_eq!(view.get_i64(95), Some(3));
+
+    let message = to_message("35=W|55=EURUSD|268=2|269=0|270=1.1|269=1|270=1.2|");
+    let view = fix.view(&message).unwrap();
+    let prices: Vec<&[u8]> = view.iter().filter(|(tag, _)| *tag == 270).map(|(_, value)| value).collect();
+    assert_eq!(prices, vec![b"1.1".as_slice(), b"1.2".as_slice()]);
+    assert_eq!(view.to_ido().unwrap().get_array(&268).unwrap().len(), 2);
+}
+
+fn heartbeat(fix: &FIX, seqnum: i64) -> Vec<u8> {
+    let mut ido = Ido::new();
+    ido.set_string(&49, "SENDER".to_string());
+    ido.set_string(&56, "TARGET".to_string());
+    ido.set_integer(&34, seqnum);
+    fix.encode(&ido, "0").unwrap()
+}
+
+#[test]
+fn test_framer_partial_reads() {
+    let fix = setup_fix();
+    let stream = [heartbeat(&fix, 1), heartbeat(&fix, 2)].concat();
+
+    let mut framer = FixFramer::new();
+    let mut frames = Vec::new();
+    for chunk in stream.chunks(5) {
+        framer.push(chunk);
+        while let Some(frame) = framer.next_frame().unwrap() {
+            frames.push(frame);
+        }
+    }
+
+    assert_eq!(frames, vec![heartbeat(&fix, 1), heartbeat(&fix, 2)]);
+    assert_eq!(framer.buffered(), 0);
+    assert_eq!(fix.decode(&frames[1]).unwrap().get_i64(&34), Some(2));
+}
+
+#[test]
+fn test_framer_skips_garbage() {
+    let fix = setup_fix();
+    let mut framer = FixFramer::new();
+    framer.push(b"garbage\x0158=FIX not a frame\x01");
+    framer.push(&heartbeat(&fix, 1));
+    framer.push(b"\r\n");
+    framer.push(&heartbeat(&fix, 2));
+
+    assert_eq!(framer.next_frame().unwrap(), Some(heartbeat(&fix, 1)));
+    assert_eq!(framer.next_frame().unwrap(), Some(heartbeat(&fix, 2)));
+    assert_eq!(framer.next_frame().unwrap(), None);
+}
+
+#[test]
+fn test_framer_resynchronises_after_corruption() {
+    let fix = setup_fix();
+    let mut framer = FixFramer::new();
+    framer.push(&to_wire("8=FIX.4.4|9=5|35=0|49=SENDER|10=123|"));
+    framer.push(&to_wire("8=FIX.4.4|9=ab|35=0|10=123|"));
+    framer.push(&heartbeat(&fix, 3));
+
+    match framer.next_frame() {
+        Err(extrans::ExtransError::FrameError(msg)) => assert!(msg.contains("CheckSum"), "{}", msg),
+        other => panic!("Expected a frame error, got {:?}", other),
+    }
+    match framer.next_frame() {
+        Err(extrans::ExtransError::FrameError(msg)) => assert!(msg.contains("invalid BodyLength"), "{}", msg),
+        other => panic!("Expected a frame error, got {:?}", other),
+    }
+    assert_eq!(framer.next_frame().unwrap(), Some(heartbeat(&fix, 3)));
+}
+
+#[test]
+fn test_framer_max_body_length() {
+    let fix = setup_fix();
+    let mut framer = FixFramer::new().with_max_body_length(10);
+    framer.push(&heartbeat(&fix, 1));
+    assert!(matches!(framer.next_frame(), Err(extrans::ExtransError::FrameError(_))));
+    assert_eq!(framer.next_frame().unwrap(), None);
+}
+
+#[test]
+fn test_decode_verifies_checksum_and_body_length() {
+    let fix = setup_fix();
+    let valid = to_message("35=0|49=SENDER|56=TARGET|34=1|");
+    assert!(fix.decode(&valid).is_ok());
+
+    let text = to_text(&valid);
+    let bad_checksum = format!("{}10=000|", &text[..text.len() - 7]);
+    match fix.decode(&to_wire(&bad_checksum)) {
+        Err(extrans::ExtransError::ChecksumMismatch(msg)) => assert!(msg.contains("'000'"), "{}", msg),
+        other => panic!("Expected a checksum mismatch, got {:?}", other.map(|ido| ido.to_string())),
+    }
+
+    let bad_body_length = to_wire("8=FIX.4.4|9=5|35=0|49=SENDER|56=TARGET|34=1|10=000|");
+    assert!(matches!(fix.decode(&bad_body_length), Err(extrans::ExtransError::BodyLengthMismatch(_))));
+    assert!(matches!(fix.view(&bad_body_length), Err(extrans::ExtransError::BodyLengthMismatch(_))));
+    assert!(matches!(fix.decode(&to_wire("35=0|49=SENDER|10=000|")), Err(extrans::ExtransError::BodyLengthMismatch(_))));
+
+    let mut fix = setup_fix();
+    fix.set_strictness(Strictness::WARN);
+    assert_eq!(fix.decode(&bad_body_length).unwrap().get_string(&49), Some("SENDER".to_string()));
+    fix.set_strictness(Strictness::IGNORE);
+    assert!(fix.decode(&to_wire(&bad_checksum)).is_ok());
+}
+
+#[test]
+fn test_strictness_from_properties() {
+    let mut fix = setup_fix();
+    assert_eq!(fix.get_strictness(), Strictness::STRICT);
+
+    let properties = extrans::properties::PropertiesBuilder::new().with_file("tests/config/fix.properties").build().unwrap();
+    fix.init(&properties).unwrap();
+    assert_eq!(fix.get_strictness(), Strictness::WARN);
+
+    let properties = extrans::properties::PropertiesBuilder::new().with_file("tests/config/extrans.properties").build().unwrap();
+    fix.init(&properties).unwrap();
+    assert_eq!(fix.get_strictness(), Strictness::STRICT);
+
+    assert_eq!("Ignore".parse::<Strictness>(), Ok(Strictness::IGNORE));
+    assert!("lenient".parse::<Strictness>().is_err());
+}
+
+fn new_order_single() -> Ido {
+    let mut ido = Ido::new();
+    ido.set_string(&49, "SENDER".to_string());
+    ido.set_string(&56, "TARGET".to_string());
+    ido.set_integer(&34, 2);
+    ido.set_datetime(&52, Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap());
+    ido.set_string(&11, "ORDER1".to_string());
+    ido.set_string(&55, "VOD.L".to_string());
+    ido.set_string(&54, "1".to_string());
+    ido.set_datetime(&60, Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap());
+    ido.set_f64(&38, 100.0);
+    ido.set_string(&40, "2".to_string());
+    ido.set_f64(&44, 101.5);
+    ido
+}
+
+#[test]
+fn test_validate_valid_message() {
+    let fix = setup_fix();
+    let mut ido = new_order_single();
+    let mut party = Ido::new();
+    party.set_string(&448, "TRADER1".to_string());
+    party.set_integer(&452, 11);
+    ido.append_array(&453, party);
+
+    assert_eq!(fix.validate(&ido, "D").unwrap(), vec![]);
+    assert!(fix.validate(&fix.decode(&fix.encode(&ido, "D").unwrap()).unwrap(), "D").unwrap().is_empty());
+}
+
+#[test]
+fn test_validate_reports_all_violations() {
+    let fix = setup_fix();
+    let mut ido = new_order_single();
+    ido.delete_item(&11);
+    ido.delete_item(&56);
+    ido.set_string(&54, "Z".to_string());
+    ido.set_string(&44, "abc".to_string());
+    ido.set_string(&9999, "X".to_string());
+    let mut party = Ido::new();
+    party.set_integer(&452, 11);
+    party.set_string(&55, "VOD.L".to_string());
+    ido.append_array(&453, party);
+
+    let context = "message D".to_string();
+    let entry = "message D group 453 entry 1".to_string();
+    assert_eq!(fix.validate(&ido, "D").unwrap(), vec![
+        Violation::MissingRequiredField { context: context.clone(), tag: 56 },
+        Violation::MissingRequiredField { context: context.clone(), tag: 11 },
+        Violation::InvalidDataType { context: context.clone(), tag: 44, field_type: FieldType::PRICE, value: "abc".to_string() },
+        Violation::InvalidEnumValue { context: context.clone(), tag: 54, value: "Z".to_string() },
+        Violation::MissingDelimiter { context: entry.clone(), tag: 453, delimiter: 448 },
+        Violation::UndefinedTag { context: entry.clone(), tag: 55 },
+        Violation::UndefinedTag { context: context.clone(), tag: 9999 },
+    ]);
+}
+
+#[test]
+fn test_validate_group_count_and_message_type() {
+    let fix = setup_fix();
+    let mut ido = new_order_single();
+    ido.set_integer(&453, 2);
+    let violations = fix.validate(&ido, "D").unwrap();
+    assert_eq!(violations, vec![Violation::GroupCountMismatch { context: "message D".to_string(), tag: 453, count: 2, entries: 0 }]);
+    assert_eq!(violations[0].to_string(), "message D: group 453 count is 2 but 0 entries are present");
+
+    // the count a decoded group declared is checked against the entries read
+    let mut party = Ido::new();
+    party.set_string(&448, "P1".to_string());
+    ido.delete_item(&453);
+    ido.append_array(&453, party.clone());
+    ido.append_array(&453, party);
+    assert!(fix.validate(&ido, "D").unwrap().is_empty());
+    for (count, entries) in [(1, 2), (0, 1)] {
+        let body = format!("35=D|49=SENDER|56=TARGET|34=2|453={}|{}", count, "448=P1|".repeat(entries));
+        let decoded = fix.decode(&to_message(&body)).unwrap();
+        assert_eq!(decoded.get_array(&453).unwrap().len(), entries);
+        ido.set_item(&453, decoded.get_item(&453).unwrap());
+        assert_eq!(fix.validate(&ido, "D").unwrap(), vec![Violation::GroupCountMismatch { context: "message D".to_string(), tag: 453, count, entries }]);
+    }
+    assert!(fix.decode(&to_message("35=D|49=SENDER|56=TARGET|34=2|453=2|448=P1|")).is_err());
+
+    assert_eq!(fix.validate(&ido, "ZZ").unwrap(), vec![Violation::UnknownMessageType { msgtype: "ZZ".to_string() }]);
+    assert!(FIX::new().validate(&ido, "D").is_err());
+}
+
+fn setup_multi_version_fix() -> FIX {
+    let mut fix = FIX::new();
+    fix.load_dictionary(DICTIONARY).unwrap();
+    fix.load_dictionary("tests/config/FIX42.xml").unwrap();
+    fix.load_dictionary("tests/config/FIX50SP2.xml").unwrap();
+    fix.load_dictionary("tests/config/FIXT11.xml").unwrap();
+    fix
+}
+
+fn new_order(begin_string: &str) -> Ido {
+    let mut ido = Ido::new();
+    ido.set_string(&8, begin_string.to_string());
+    ido.set_string(&49, "SENDER".to_string());
+    ido.set_string(&56, "TARGET".to_string());
+    ido.set_integer(&34, 1);
+    ido.set_string(&52, "20240101-12:00:00.000".to_string());
+    ido.set_string(&11, "ORDER1".to_string());
+    ido.set_string(&21, "1".to_string());
+    ido.set_string(&55, "VOD.L".to_string());
+    ido.set_string(&54, "1".to_string());
+    ido.set_string(&60, "20240101-12:00:00.000".to_string());
+    ido.set_string(&40, "1".to_string());
+    ido
+}
+
+#[test]
+fn test_multiple_versions() {
+    let fix = setup_multi_version_fix();
+
+    assert_eq!(fix.get_dictionary().unwrap().get_begin_string(), "FIX.4.4");
+    assert_eq!(fix.get_dictionary_for("FIX.4.2", None).unwrap().get_appl_ver_id(), Some("4"));
+    assert_eq!(fix.get_dictionary_for("FIX.5.0SP2", None).unwrap().get_appl_ver_id(), Some("9"));
+    assert!(fix.get_dictionary_for("FIXT.1.1", Some("9")).unwrap().get_message("D").is_some());
+    assert!(fix.get_dictionary_for("FIX.4.3", None).is_none());
+
+    let fix42 = to_text(&fix.encode(&new_order("FIX.4.2"), "D").unwrap());
+    assert!(fix42.starts_with("8=FIX.4.2|"), "{}", fix42);
+    assert!(fix42.contains("|11=ORDER1|21=1|55=VOD.L|"), "{}", fix42);
+    assert_eq!(fix.validate(&new_order("FIX.4.2"), "D").unwrap(), vec![]);
+
+    let fix44 = to_text(&fix.encode(&new_order("FIX.4.4"), "D").unwrap());
+    assert!(fix44.starts_with("8=FIX.4.4|"), "{}", fix44);
+
+    let decoded = fix.decode(&to_wire(&fix42)).unwrap();
+    assert_eq!(decoded.get_string(&21), Some("1".to_string()));
+    match fix.decode(&to_wire("8=FIX.4.3|9=5|35=0|10=000|")) {
+        Err(extrans::ExtransError::DecodeError(msg)) => assert!(msg.contains("'FIX.4.3'"), "{}", msg),
+        other => panic!("Expected a decode error, got {:?}", other.map(|ido| ido.to_string())),
+    }
+}
+
+#[test]
+fn test_fixt_transport_and_application() {
+    let fix = setup_multi_version_fix();
+    let transport = fix.get_dictionary_for("FIXT.1.1", Some("9")).unwrap();
+    assert!(transport.is_header_field(1128));
+    assert!(transport.get_message("A").is_some(), "session messages come from the transport");
+
+    let mut order = new_order("FIXT.1.1");
+    order.delete_item(&21);
+    order.set_string(&1128, "9".to_string());
+    order.set_string(&48, "GB00BH4HKS39".to_string());
+    let encoded = to_text(&fix.encode(&order, "D").unwrap());
+    assert!(encoded.starts_with("8=FIXT.1.1|"), "{}", encoded);
+    assert!(encoded.contains("|35=D|1128=9|49=SENDER|"), "{}", encoded);
+    assert!(encoded.contains("|11=ORDER1|55=VOD.L|48=GB00BH4HKS39|54=1|"), "{}", encoded);
+
+    let decoded = fix.decode(&to_wire(&encoded)).unwrap();
+    assert_eq!(decoded.get_string(&48), Some("GB00BH4HKS39".to_string()));
+    assert_eq!(fix.validate(&decoded, "D").unwrap(), vec![]);
+
+    // without ApplVerID the default application version, FIX.5.0SP2, is used
+    order.delete_item(&1128);
+    assert!(fix.encode(&order, "D").is_ok());
+
+    let mut logon = Ido::new();
+    logon.set_string(&8, "FIXT.1.1".to_string());
+    logon.set_integer(&98, 0);
+    logon.set_integer(&108, 30);
+    logon.set_string(&1137, "9".to_string());
+    let decoded = fix.decode(&fix.encode(&logon, "A").unwrap()).unwrap();
+    assert_eq!(decoded.get_string(&1137), Some("9".to_string()));
+
+    order.set_string(&1128, "7".to_string());
+    assert!(matches!(fix.encode(&order, "D"), Err(extrans::ExtransError::EncodeError(_))));
+}
+
+#[test]
+fn test_dictionary_overlay() {
+    let mut dictionary = Dictionary::new();
+    dictionary.load(DICTIONARY).unwrap();
+    let issues = dictionary.load_overlay("tests/config/FIX44_venue.xml").unwrap();
+
+    assert_eq!(issues, vec![
+        DictionaryIssue::ConflictingFieldType { name: "HopRefID".to_string(), tag: 630, field_type: "STRING".to_string(), existing: "SEQNUM".to_string() },
+        DictionaryIssue::DuplicateTag { tag: 803, name: "VenueSubIDType".to_string(), existing: "PartySubIDType".to_string() },
+        DictionaryIssue::ConflictingMessageName { msgtype: "0".to_string(), name: "VenueHeartbeat".to_string(), existing: "Heartbeat".to_string() },
+    ]);
+    let validated = dictionary.validate();
+    for issue in issues.iter() {
+        assert!(validated.contains(issue), "missing {} in {:?}", issue, validated);
+    }
+
+    // fields are added and overridden, enumerations merged
+    assert_eq!(*dictionary.get_field(5005).unwrap().get_field_type(), FieldType::PERCENTAGE);
+    assert_eq!(*dictionary.get_field(630).unwrap().get_field_type(), FieldType::STRING);
+    let side = dictionary.get_field(54).unwrap();
+    assert_eq!(side.get_value_description("1"), Some("BUY_VENUE"));
+    assert_eq!(side.get_value_description("2"), Some("SELL"));
+    assert_eq!(side.get_value_description("Z"), Some("VENUE_CROSS"));
+
+    // message definitions are merged and resolved again
+    let order = dictionary.get_message("D").unwrap();
+    assert!(order.is_required(1));
+    assert!(order.is_required(5001));
+    assert!(order.has_tag(11) && order.has_tag(44));
+    assert!(order.has_tag(5006), "component fields are merged");
+    assert!(order.get_group(5003).unwrap().has_tag(5005));
+    assert_eq!(dictionary.get_message("U1").unwrap().get_name(), "VenueStatus");
+    assert_eq!(dictionary.get_message("0").unwrap().get_name(), "VenueHeartbeat");
+    assert!(dictionary.get_message("0").unwrap().has_tag(112));
+    assert!(dictionary.is_header_field(5007));
+}
+
+#[test]
+fn test_fix_overlay_encode_decode() {
+    let mut fix = setup_fix();
+    assert!(fix.load_overlay("FIX.4.2", "tests/config/FIX44_venue.xml").is_err());
+    assert!(fix.load_overlay("FIX.4.4", "invalid_file.xml").is_err());
+    fix.load_overlay("FIX.4.4", "tests/config/FIX44_venue.xml").unwrap();
+
+    let mut ido = new_order_single();
+    ido.set_string(&1, "ACC1".to_string());
+    ido.set_string(&5007, "S1".to_string());
+    assert_eq!(fix.validate(&ido, "D").unwrap(), vec![
+        Violation::MissingRequiredField { context: "message D".to_string(), tag: 5001 },
+    ]);
+
+    ido.set_string(&5001, "TAG1".to_string());
+    let mut route = Ido::new();
+    route.set_string(&5004, "LIT".to_string());
+    route.set_f64(&5005, 0.5);
+    ido.append_array(&5003, route);
+    assert_eq!(fix.validate(&ido, "D").unwrap(), vec![]);
+
+    let encoded = to_text(&fix.encode(&ido, "D").unwrap());
+    assert!(encoded.contains("|5007=S1|"), "{}", encoded);
+    assert!(encoded.contains("|5001=TAG1|5003=1|5004=LIT|5005=0.5|"), "{}", encoded);
+
+    let decoded = fix.decode(&to_wire(&encoded)).unwrap();
+    assert_eq!(decoded.get_string(&5001), Some("TAG1".to_string()));
+    assert_eq!(decoded.get_array(&5003).unwrap()[0].get_f64(&5005), Some(0.5));
+}
+
+fn temp_path(name: &str) -> String {
+    let path = format!("{}/{}", env!("CARGO_TARGET_TMPDIR"), name);
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+fn assert_same_dictionary(expected: &Dictionary, actual: &Dictionary) {
+    assert_eq!(expected.get_begin_string(), actual.get_begin_string());
+    assert_eq!(expected.get_appl_ver_id(), actual.get_appl_ver_id());
+    assert_eq!(expected.iter_fields().count(), actual.iter_fields().count());
+    for field in expected.iter_fields() {
+        let actual_field = actual.get_field(field.get_tag()).unwrap();
+        assert_eq!(field.to_string(), actual_field.to_string());
+        assert_eq!(field.iter_values().as_slice(), actual_field.iter_values().as_slice());
+        assert_eq!((field.get_length_tag(), field.get_data_tag()), (actual_field.get_length_tag(), actual_field.get_data_tag()));
+    }
+    assert_eq!(expected.iter_messages().count(), actual.iter_messages().count());
+    for message in expected.iter_messages() {
+        let actual_message = actual.get_message(message.get_type()).unwrap();
+        assert_eq!(message.to_string(), actual_message.to_string());
+        for tag in message.get_tags() {
+            assert_eq!(message.is_required(tag), actual_message.is_required(tag), "{} tag {}", message.get_name(), tag);
+        }
+    }
+    assert_eq!(expected.get_header().to_string(), actual.get_header().to_string());
+    assert_eq!(expected.get_trailer().to_string(), actual.get_trailer().to_string());
+    assert_eq!(expected.validate(), actual.validate());
+}
+
+#[test]
+fn test_dictionary_cache() {
+    for source in [DICTIONARY, "tests/config/FIX50SP2.xml", "tests/config/FIX44_broken.xml"] {
+        let cache = temp_path(&format!("{}.cache", source.rsplit('/').next().unwrap()));
+        let mut expected = Dictionary::new();
+        expected.load(source).unwrap();
+
+        let mut built = Dictionary::new();
+        built.load_cached(source, &cache).unwrap();
+        assert!(std::path::Path::new(&cache).exists());
+        assert_same_dictionary(&expected, &built);
+
+        let mut cached = Dictionary::new();
+        cached.load_cached(source, &cache).unwrap();
+        assert_same_dictionary(&expected, &cached);
+    }
+
+    let cache = temp_path("FIX44.fix.cache");
+    let mut fix = FIX::new();
+    fix.load_dictionary_cached(DICTIONARY, &cache).unwrap();
+    fix.load_dictionary_cached(DICTIONARY, &cache).unwrap();
+    let mut ido = new_order_single();
+    let mut party = Ido::new();
+    party.set_string(&448, "TRADER1".to_string());
+    party.set_integer(&452, 11);
+    ido.append_array(&453, party);
+    let encoded = fix.encode(&ido, "D").unwrap();
+    assert_eq!(encoded, setup_fix().encode(&ido, "D").unwrap());
+    assert_eq!(fix.validate(&fix.decode(&encoded).unwrap(), "D").unwrap(), vec![]);
+}
+
+#[test]
+fn test_dictionary_cache_invalidation() {
+    let source = temp_path("FIX44_source.xml");
+    let cache = temp_path("FIX44_source.xml.cache");
+    let original = std::fs::read_to_string(DICTIONARY).unwrap();
+    std::fs::write(&source, &original).unwrap();
+
+    let mut dictionary = Dictionary::new();
+    dictionary.load_cached(&source, &cache).unwrap();
+    assert!(dictionary.get_field(5001).is_none());
+
+    // the source XML changes, the cache is rebuilt
+    let changed = original.replace("<fields>", "<fields>\n  <field number=\"5001\" name=\"VenueOrderTag\" type=\"STRING\"/>");
+    std::fs::write(&source, changed).unwrap();
+    let mut dictionary = Dictionary::new();
+    dictionary.load_cached(&source, &cache).unwrap();
+    assert!(dictionary.get_field(5001).is_some());
+    let mut dictionary = Dictionary::new();
+    dictionary.load_cached(&source, &cache).unwrap();
+    assert!(dictionary.get_field(5001).is_some());
+
+    // a corrupt or truncated cache is ignored and rewritten
+    let data = std::fs::read(&cache).unwrap();
+    std::fs::write(&cache, &data[..data.len() / 2]).unwrap();
+    let mut dictionary = Dictionary::new();
+    dictionary.load_cached(&source, &cache).unwrap();
+    assert!(dictionary.get_message("D").is_some());
+    assert_eq!(std::fs::read(&cache).unwrap(), data);
+
+    std::fs::write(&cache, b"not a cache").unwrap();
+    let mut dictionary = Dictionary::new();
+    dictionary.load_cached(&source, &cache).unwrap();
+    assert_eq!(std::fs::read(&cache).unwrap(), data);
+
+    let mut dictionary = Dictionary::new();
+    assert!(dictionary.load_cached("invalid_file.xml", &cache).is_err());
+}
+
+#[test]
+fn test_load_orchestra_repository() {
+    let mut quickfix = Dictionary::new();
+    quickfix.load(DICTIONARY).unwrap();
+    let mut orchestra = Dictionary::new();
+    orchestra.load("tests/config/FIX44_orchestra.xml").unwrap();
+
+    assert_eq!(orchestra.get_begin_string(), "FIX.4.4");
+    assert_eq!(orchestra.get_appl_ver_id(), Some("6"));
+    assert!(orchestra.validate().is_empty(), "{:?}", orchestra.validate());
+
+    // messages, groups and presence rules resolve to the same layouts as the QuickFIX dictionary
+    for msgtype in ["0", "A", "D"] {
+        let expected = quickfix.get_message(msgtype).unwrap();
+        let message = orchestra.get_message(msgtype).unwrap();
+        assert_eq!(message.to_string(), expected.to_string());
+        for tag in expected.get_tags() {
+            assert_eq!(message.is_required(tag), expected.is_required(tag), "{} tag {}", msgtype, tag);
+        }
+    }
+    assert_eq!(orchestra.get_header().to_string(), quickfix.get_header().to_string());
+    assert_eq!(orchestra.get_trailer().to_string(), quickfix.get_trailer().to_string());
+    assert!(orchestra.get_message("A").unwrap().is_required(98), "constant fields are required");
+    assert!(!orchestra.get_message("0").unwrap().has_tag(58), "forbidden fields are left out");
+    assert!(orchestra.get_message("D").unwrap().has_tag(44), "only the base scenario is loaded");
+    assert_eq!(orchestra.get_message("0").unwrap().get_category(), "admin");
+    assert_eq!(orchestra.get_message("D").unwrap().get_category(), "app");
+
+    // codeSets become field values, data fields are linked to their length
+    for tag in [22, 40, 43, 54, 452] {
+        let expected = quickfix.get_field(tag).unwrap();
+        let field = orchestra.get_field(tag).unwrap();
+        assert_eq!(field.to_string(), expected.to_string());
+        assert_eq!(field.iter_values().as_slice(), expected.iter_values().as_slice());
+    }
+    assert_eq!(orchestra.get_field(96).unwrap().get_length_tag(), Some(95));
+
+    let mut fix = FIX::new();
+    fix.load_dictionary("tests/config/FIX44_orchestra.xml").unwrap();
+    let mut ido = new_order_single();
+    let mut party = Ido::new();
+    party.set_string(&448, "TRADER1".to_string());
+    party.set_integer(&452, 11);
+    ido.append_array(&453, party);
+    let encoded = fix.encode(&ido, "D").unwrap();
+    assert_eq!(encoded, setup_fix().encode(&ido, "D").unwrap());
+    assert_eq!(fix.validate(&fix.decode(&encoded).unwrap(), "D").unwrap(), vec![]);
+}
+
+#[test]
+fn test_load_orchestra_repository_issues() {
+    let source = temp_path("orchestra_broken.xml");
+    std::fs::write(&source, r#"<fixr:repository xmlns:fixr="http://fixprotocol.io/2020/orchestra/repository" version="FIX.5.0SP2_EP254">
+ <fixr:fields>
+  <fixr:field id="11" name="ClOrdID" type="String"/>
+  <fixr:field id="X" name="Broken" type="String"/>
+  <fixr:field id="453" name="NoPartyIDs" type="NumInGroup"/>
+ </fixr:fields>
+ <fixr:groups>
+  <fixr:group id="1012" name="Parties"><fixr:numInGroup id="453"/><fixr:groupRef id="1012"/></fixr:group>
+ </fixr:groups>
+ <fixr:messages>
+  <fixr:message name="NewOrderSingle" msgType="D" category="SingleGeneralOrderHandling">
+   <fixr:structure>
+    <fixr:fieldRef id="11" presence="required"/>
+    <fixr:fieldRef id="999"/>
+    <fixr:componentRef id="1003"/>
+   </fixr:structure>
+  </fixr:message>
+  <fixr:message name="Cyclic" msgType="U1">
+   <fixr:structure><fixr:groupRef id="1012"/></fixr:structure>
+  </fixr:message>
+ </fixr:messages>
+</fixr:repository>"#).unwrap();
+
+    let mut dictionary = Dictionary::new();
+    match dictionary.load(&source) {
+        Err(extrans::ExtransError::SetupError(msg)) => assert!(msg.contains("cyclic group reference"), "{}", msg),
+        _ => panic!("expected a cyclic group reference error"),
+    }
+
+    std::fs::write(&source, std::fs::read_to_string(&source).unwrap().replace("<fixr:groupRef id=\"1012\"/></fixr:group>", "</fixr:group>")).unwrap();
+    let mut dictionary = Dictionary::new();
+    dictionary.load(&source).unwrap();
+    assert_eq!(dictionary.get_begin_string(), "FIX.5.0SP2");
+    assert_eq!(dictionary.get_appl_ver_id(), Some("9"));
+    assert_eq!(dictionary.validate(), vec![
+        DictionaryIssue::InvalidAttribute { context: "field Broken".to_string(), attribute: "id".to_string(), value: "X".to_string() },
+        DictionaryIssue::UnknownField { context: "message NewOrderSingle".to_string(), name: "999".to_string() },
+        DictionaryIssue::UndefinedComponent { context: "message NewOrderSingle".to_string(), name: "1003".to_string() },
+    ]);
+    assert!(dictionary.get_message("D").unwrap().is_required(11));
+    assert!(dictionary.get_message("U1").unwrap().get_group(453).is_some());
+}
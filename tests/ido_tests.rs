@@ -22,6 +22,7 @@ fn test_set_item() {
 }
 
 #[test]
+#[allow(clippy::assertions_on_constants)]
 fn test_set_string() {
     let mut d = Ido::new();
 
@@ -42,6 +43,7 @@ fn test_set_string() {
 }
 
 #[test]
+#[allow(clippy::assertions_on_constants)]
 fn test_set_string_overwrite() {
     let mut ido = Ido::new();
     let key: IdoKeyT = 123;
@@ -60,6 +62,7 @@ fn test_set_string_overwrite() {
 }
 
 #[test]
+#[allow(clippy::assertions_on_constants)]
 fn test_set_empty_string() {
     let mut ido = Ido::new();
     let key: IdoKeyT = 123;
@@ -86,6 +89,7 @@ fn test_set_integer() {
 }
 
 #[test]
+#[allow(clippy::approx_constant)]
 fn test_set_f64() {
     let mut ido = Ido::new();
     ido.set_f64(&1, 3.14159);
@@ -315,6 +319,7 @@ fn test_get_u8() {
 }
 
 #[test]
+#[allow(clippy::approx_constant)]
 fn test_get_f64() {
     let mut ido = Ido::new();
     ido.set_f64(&42, 3.14);
@@ -0,0 +1,629 @@
+use extrans::{FIX, Extrans};
+use extrans::ido::Ido;
+use extrans::fix::Strictness;
+use extrans::fix::session::{FileStore, MessageStore, Session, SessionAction, SessionID, SessionRole, SessionSchedule, SessionSettings, SessionState};
+use extrans::properties::PropertiesBuilder;
+use chrono::{DateTime, Duration, NaiveTime, TimeZone, Utc, Weekday};
+use std::io::Write;
+
+const DICTIONARY: &str = "tests/config/FIX44.xml";
+
+fn setup_fix() -> FIX {
+    let mut fix = FIX::new();
+    fix.load_dictionary(DICTIONARY).unwrap();
+    fix
+}
+
+fn start() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap()
+}
+
+fn initiator() -> Session {
+    Session::new(SessionID::new("FIX.4.4", "CLIENT", "BROKER"), SessionRole::INITIATOR, 30)
+}
+
+fn acceptor() -> Session {
+    Session::new(SessionID::new("FIX.4.4", "BROKER", "CLIENT"), SessionRole::ACCEPTOR, 60)
+}
+
+/// Returns the messages the actions send, encoded and decoded again as they would be on the wire.
+fn sent(fix: &FIX, actions: &[SessionAction]) -> Vec<Ido> {
+    actions.iter().filter_map(|action| match action {
+        SessionAction::Send { msgtype, message } => Some(fix.decode(&fix.encode(message, msgtype).unwrap()).unwrap()),
+        _ => None,
+    }).collect()
+}
+
+fn msgtypes(actions: &[SessionAction]) -> Vec<String> {
+    actions.iter().map(|action| match action {
+        SessionAction::Send { msgtype, .. } => format!("send {}", msgtype),
+        SessionAction::Deliver { msgtype, .. } => format!("deliver {}", msgtype),
+        SessionAction::LoggedOn => "logged on".to_string(),
+        SessionAction::LoggedOut => "logged out".to_string(),
+        SessionAction::Disconnect { .. } => "disconnect".to_string(),
+    }).collect()
+}
+
+/// Delivers every message sent by the actions to `session`, returning its actions.
+fn deliver(fix: &FIX, actions: &[SessionAction], session: &mut Session, now: DateTime<Utc>) -> Vec<SessionAction> {
+    sent(fix, actions).into_iter().flat_map(|message| session.on_message(message, now)).collect()
+}
+
+/// Returns an empty directory under the cargo temporary directory.
+fn temp_dir(name: &str) -> String {
+    let path = format!("{}/{}", env!("CARGO_TARGET_TMPDIR"), name);
+    let _ = std::fs::remove_dir_all(&path);
+    path
+}
+
+fn logged_on_pair(fix: &FIX) -> (Session, Session) {
+    let (mut client, mut broker) = (initiator(), acceptor());
+    broker.on_connect(start());
+    let logon = client.on_connect(start());
+    let response = deliver(fix, &logon, &mut broker, start());
+    deliver(fix, &response, &mut client, start());
+    (client, broker)
+}
+
+#[test]
+fn test_session_logon() {
+    let fix = setup_fix();
+    let (mut client, mut broker) = (initiator(), acceptor());
+    assert_eq!(client.get_state(), SessionState::DISCONNECTED);
+
+    assert!(broker.on_connect(start()).is_empty());
+    assert_eq!(broker.get_state(), SessionState::AWAITING_LOGON);
+
+    let logon = client.on_connect(start());
+    assert_eq!(msgtypes(&logon), vec!["send A"]);
+    assert_eq!(client.get_state(), SessionState::LOGON_SENT);
+    let message = &sent(&fix, &logon)[0];
+    assert_eq!(message.get_string(&49), Some("CLIENT".to_string()));
+    assert_eq!(message.get_string(&56), Some("BROKER".to_string()));
+    assert_eq!(message.get_i64(&34), Some(1));
+    assert_eq!(message.get_i64(&108), Some(30));
+    assert_eq!(message.get_datetime(&52), Some(start()));
+
+    let response = deliver(&fix, &logon, &mut broker, start());
+    assert_eq!(msgtypes(&response), vec!["send A", "logged on"]);
+    assert!(broker.is_logged_on());
+    assert_eq!(broker.get_heartbeat_interval(), 30, "the acceptor adopts the initiator's HeartBtInt");
+    assert_eq!(sent(&fix, &response)[0].get_string(&49), Some("BROKER".to_string()));
+
+    assert_eq!(msgtypes(&deliver(&fix, &response, &mut client, start())), vec!["logged on"]);
+    assert!(client.is_logged_on());
+    assert_eq!(client.get_next_sender_seq_num(), 2);
+    assert_eq!(client.get_next_target_seq_num(), 2);
+}
+
+#[test]
+fn test_session_heartbeat_and_test_request() {
+    let fix = setup_fix();
+    let (mut client, mut broker) = logged_on_pair(&fix);
+
+    assert!(client.on_timer(start() + Duration::seconds(29)).is_empty());
+    let heartbeat = client.on_timer(start() + Duration::seconds(30));
+    assert_eq!(msgtypes(&heartbeat), vec!["send 0"]);
+    assert!(deliver(&fix, &heartbeat, &mut broker, start() + Duration::seconds(30)).is_empty());
+
+    // nothing received for HeartBtInt plus 20%, a TestRequest is sent and answered
+    let now = start() + Duration::seconds(36);
+    let test_request = client.on_timer(now);
+    assert_eq!(msgtypes(&test_request), vec!["send 1"]);
+    let test_req_id = sent(&fix, &test_request)[0].get_string(&112).unwrap();
+    let response = deliver(&fix, &test_request, &mut broker, now);
+    assert_eq!(msgtypes(&response), vec!["send 0"]);
+    assert_eq!(sent(&fix, &response)[0].get_string(&112), Some(test_req_id));
+    deliver(&fix, &response, &mut client, now);
+    assert!(client.on_timer(now + Duration::seconds(30)).iter().all(|a| matches!(a, SessionAction::Send { msgtype, .. } if msgtype == "0")));
+
+    // an unanswered TestRequest ends the session
+    let now = now + Duration::seconds(36);
+    assert_eq!(msgtypes(&client.on_timer(now)), vec!["send 1"]);
+    assert!(client.on_timer(now + Duration::seconds(35)).iter().all(|a| !matches!(a, SessionAction::Disconnect { .. })));
+    let actions = client.on_timer(now + Duration::seconds(36));
+    assert_eq!(msgtypes(&actions), vec!["send 5", "logged out", "disconnect"]);
+    assert_eq!(client.get_state(), SessionState::DISCONNECTED);
+}
+
+#[test]
+fn test_session_logout() {
+    let fix = setup_fix();
+    let (mut client, mut broker) = logged_on_pair(&fix);
+
+    let logout = client.logout(Some("end of day"), start());
+    assert_eq!(msgtypes(&logout), vec!["send 5"]);
+    assert_eq!(client.get_state(), SessionState::LOGOUT_SENT);
+    assert_eq!(sent(&fix, &logout)[0].get_string(&58), Some("end of day".to_string()));
+
+    // messages sent before the counterparty saw the logout are still delivered
+    let order = broker.send("8", Ido::new(), start()).unwrap();
+    assert_eq!(msgtypes(&deliver(&fix, &order, &mut client, start())), vec!["deliver 8"]);
+
+    let response = deliver(&fix, &logout, &mut broker, start());
+    assert_eq!(msgtypes(&response), vec!["send 5", "logged out", "disconnect"]);
+    assert_eq!(broker.get_state(), SessionState::DISCONNECTED);
+
+    assert_eq!(msgtypes(&deliver(&fix, &response, &mut client, start())), vec!["logged out", "disconnect"]);
+    assert_eq!(client.get_state(), SessionState::DISCONNECTED);
+
+    // without a confirmation the logout times out
+    let (mut client, _) = logged_on_pair(&fix);
+    client.logout(None, start());
+    assert!(client.on_timer(start() + Duration::seconds(9)).is_empty());
+    assert_eq!(msgtypes(&client.on_timer(start() + Duration::seconds(10))), vec!["logged out", "disconnect"]);
+}
+
+#[test]
+fn test_session_application_messages() {
+    let fix = setup_fix();
+    let (mut client, mut broker) = (initiator(), acceptor());
+    assert!(matches!(client.send("D", Ido::new(), start()), Err(extrans::ExtransError::SessionError(_))));
+
+    let (mut client_on, mut broker_on) = logged_on_pair(&fix);
+    let mut order = Ido::new();
+    order.set_string(&11, "ORDER1".to_string());
+    let actions = client_on.send("D", order, start()).unwrap();
+    let delivered = deliver(&fix, &actions, &mut broker_on, start());
+    match &delivered[..] {
+        [SessionAction::Deliver { msgtype, message }] => {
+            assert_eq!(msgtype, "D");
+            assert_eq!(message.get_string(&11), Some("ORDER1".to_string()));
+            assert_eq!(message.get_i64(&34), Some(2));
+        },
+        _ => panic!("expected the order to be delivered: {:?}", msgtypes(&delivered)),
+    }
+
+    // the first message must be a Logon
+    broker.on_connect(start());
+    client.on_connect(start());
+    let mut heartbeat = Ido::new();
+    heartbeat.set_string(&35, "0".to_string());
+    assert_eq!(msgtypes(&broker.on_message(heartbeat, start())), vec!["disconnect"]);
+}
+
+#[test]
+fn test_session_rejects_wrong_comp_ids_and_logon_timeout() {
+    let fix = setup_fix();
+    let (_, mut broker) = logged_on_pair(&fix);
+    let mut message = Ido::new();
+    message.set_string(&35, "0".to_string());
+    message.set_string(&49, "SOMEONE".to_string());
+    message.set_string(&56, "BROKER".to_string());
+    assert_eq!(msgtypes(&broker.on_message(message, start())), vec!["send 5", "logged out", "disconnect"]);
+
+    let mut client = initiator();
+    client.set_logon_timeout(Duration::seconds(5));
+    client.on_connect(start());
+    assert!(client.on_timer(start() + Duration::seconds(4)).is_empty());
+    assert_eq!(msgtypes(&client.on_timer(start() + Duration::seconds(5))), vec!["disconnect"]);
+    assert_eq!(client.get_state(), SessionState::DISCONNECTED);
+}
+
+#[test]
+fn test_session_rejects_absurd_heartbeat_interval() {
+    let fix = setup_fix();
+    let mut broker = acceptor();
+    broker.on_connect(start());
+    let mut logon = sent(&fix, &initiator().on_connect(start())).remove(0);
+    logon.set_integer(&108, 9_000_000_000_000_000);
+    assert_eq!(msgtypes(&broker.on_message(logon, start())), vec!["disconnect"]);
+    assert_eq!(broker.get_state(), SessionState::DISCONNECTED);
+
+    // an interval of its own too long to count in milliseconds never expires
+    let mut broker = acceptor();
+    broker.on_connect(start());
+    let response = deliver(&fix, &initiator().on_connect(start()), &mut broker, start());
+    let mut client = Session::new(SessionID::new("FIX.4.4", "CLIENT", "BROKER"), SessionRole::INITIATOR, 9_000_000_000_000_000);
+    client.on_connect(start());
+    assert_eq!(msgtypes(&deliver(&fix, &response, &mut client, start())), vec!["logged on"]);
+    assert!(client.on_timer(start() + Duration::days(365)).is_empty());
+}
+
+#[test]
+fn test_session_gap_detection_and_resend() {
+    let fix = setup_fix();
+    let (mut client, mut broker) = logged_on_pair(&fix);
+    let later = start() + Duration::seconds(30);
+
+    let mut order = Ido::new();
+    order.set_string(&11, "ORDER1".to_string());
+    let first = client.send("D", order.clone(), start()).unwrap();
+    let heartbeat = client.on_timer(later);
+    assert_eq!(msgtypes(&heartbeat), vec!["send 0"]);
+    order.set_string(&11, "ORDER2".to_string());
+    let second = client.send("D", order, later).unwrap();
+    assert_eq!(client.get_next_sender_seq_num(), 5);
+
+    // the heartbeat is lost, the second order is queued and a resend requested
+    assert_eq!(msgtypes(&deliver(&fix, &first, &mut broker, later)), vec!["deliver D"]);
+    let resend_request = deliver(&fix, &second, &mut broker, later);
+    assert_eq!(msgtypes(&resend_request), vec!["send 2"]);
+    assert!(broker.is_resend_pending());
+    assert_eq!(broker.get_next_target_seq_num(), 3);
+    let message = &sent(&fix, &resend_request)[0];
+    assert_eq!(message.get_i64(&7), Some(3));
+    assert_eq!(message.get_i64(&16), Some(0));
+
+    // the heartbeat is gap filled and the order resent as a possible duplicate
+    let now = later + Duration::seconds(1);
+    let replay = deliver(&fix, &resend_request, &mut client, now);
+    assert_eq!(msgtypes(&replay), vec!["send 4", "send D"]);
+    let messages = sent(&fix, &replay);
+    assert_eq!(messages[0].get_i64(&34), Some(3));
+    assert_eq!(messages[0].get_string(&123), Some("Y".to_string()));
+    assert_eq!(messages[0].get_string(&43), Some("Y".to_string()));
+    assert_eq!(messages[0].get_i64(&36), Some(4));
+    assert_eq!(messages[1].get_i64(&34), Some(4));
+    assert_eq!(messages[1].get_string(&11), Some("ORDER2".to_string()));
+    assert_eq!(messages[1].get_string(&43), Some("Y".to_string()));
+    assert_eq!(messages[1].get_datetime(&122), Some(later));
+    assert_eq!(messages[1].get_datetime(&52), Some(now));
+    assert_eq!(client.get_next_sender_seq_num(), 5, "resending does not consume sequence numbers");
+
+    // the gap fill releases the queued order, its duplicate is ignored
+    let delivered = broker.on_message(messages[0].clone(), now);
+    match &delivered[..] {
+        [SessionAction::Deliver { message, .. }] => {
+            assert_eq!(message.get_string(&11), Some("ORDER2".to_string()));
+            assert_eq!(message.get_string(&43), None);
+        },
+        _ => panic!("expected the queued order to be delivered: {:?}", msgtypes(&delivered)),
+    }
+    assert!(broker.on_message(messages[1].clone(), now).is_empty());
+    assert!(!broker.is_resend_pending());
+    assert_eq!(broker.get_next_target_seq_num(), 5);
+    assert!(broker.is_logged_on());
+}
+
+#[test]
+fn test_session_logon_with_gap() {
+    let fix = setup_fix();
+    let (mut client, mut broker) = logged_on_pair(&fix);
+    client.send("D", Ido::new(), start()).unwrap();
+    client.on_disconnect(start());
+    broker.on_disconnect(start());
+
+    // the order sent before the disconnect never arrived
+    broker.on_connect(start());
+    let logon = client.on_connect(start());
+    let response = deliver(&fix, &logon, &mut broker, start());
+    assert_eq!(msgtypes(&response), vec!["send A", "logged on", "send 2"]);
+    assert_eq!(sent(&fix, &response)[1].get_i64(&7), Some(2));
+    assert_eq!(msgtypes(&deliver(&fix, &response, &mut client, start())), vec!["logged on", "send D", "send 4"]);
+}
+
+#[test]
+fn test_session_seq_num_too_low_and_poss_dup() {
+    let fix = setup_fix();
+    let (mut client, mut broker) = logged_on_pair(&fix);
+    let order = sent(&fix, &client.send("D", Ido::new(), start()).unwrap()).remove(0);
+    assert_eq!(msgtypes(&broker.on_message(order.clone(), start())), vec!["deliver D"]);
+
+    // a possible duplicate of a message already received is ignored
+    let mut duplicate = order.clone();
+    duplicate.set_string(&43, "Y".to_string());
+    let mut with_orig_sending_time = duplicate.clone();
+    with_orig_sending_time.set_datetime(&122, start());
+    assert!(broker.on_message(with_orig_sending_time, start()).is_empty());
+
+    // a possible duplicate needs an OrigSendingTime no later than its SendingTime
+    let actions = broker.on_message(duplicate.clone(), start());
+    assert_eq!(msgtypes(&actions), vec!["send 3"]);
+    let reject = &sent(&fix, &actions)[0];
+    assert_eq!(reject.get_i64(&45), Some(2));
+    assert_eq!(reject.get_i64(&371), Some(122));
+    assert_eq!(reject.get_i64(&373), Some(1));
+    duplicate.set_datetime(&122, start() + Duration::seconds(1));
+    let actions = broker.on_message(duplicate, start());
+    assert_eq!(msgtypes(&actions), vec!["send 3", "send 5", "logged out", "disconnect"]);
+    assert_eq!(sent(&fix, &actions)[0].get_i64(&373), Some(10));
+
+    // without PossDupFlag a sequence number that is too low ends the session
+    let (_, mut broker) = logged_on_pair(&fix);
+    let actions = broker.on_message(order, start());
+    assert_eq!(msgtypes(&actions), vec!["deliver D"]);
+    let mut replayed = sent(&fix, &client.send("D", Ido::new(), start()).unwrap()).remove(0);
+    replayed.set_integer(&34, 2);
+    assert_eq!(msgtypes(&broker.on_message(replayed, start())), vec!["send 5", "logged out", "disconnect"]);
+}
+
+#[test]
+fn test_session_sequence_reset() {
+    let fix = setup_fix();
+    let (_, mut broker) = logged_on_pair(&fix);
+    let mut reset = Ido::new();
+    reset.set_string(&35, "4".to_string());
+    reset.set_integer(&34, 1);
+    reset.set_integer(&36, 10);
+
+    // a reset applies whatever its MsgSeqNum
+    assert!(broker.on_message(reset.clone(), start()).is_empty());
+    assert_eq!(broker.get_next_target_seq_num(), 10);
+
+    // but may not move the sequence number back
+    reset.set_integer(&36, 5);
+    let actions = broker.on_message(reset, start());
+    assert_eq!(msgtypes(&actions), vec!["send 3"]);
+    assert_eq!(sent(&fix, &actions)[0].get_i64(&373), Some(5));
+    assert_eq!(broker.get_next_target_seq_num(), 10);
+
+    // a gap fill in sequence skips ahead
+    let mut gap_fill = Ido::new();
+    gap_fill.set_string(&35, "4".to_string());
+    gap_fill.set_integer(&34, 10);
+    gap_fill.set_string(&123, "Y".to_string());
+    gap_fill.set_integer(&36, 15);
+    assert!(broker.on_message(gap_fill.clone(), start()).is_empty());
+    assert_eq!(broker.get_next_target_seq_num(), 15);
+
+    // a gap fill is a possible duplicate that needs no OrigSendingTime
+    gap_fill.set_integer(&34, 15);
+    gap_fill.set_string(&43, "Y".to_string());
+    gap_fill.set_integer(&36, 20);
+    assert!(broker.on_message(gap_fill, start()).is_empty());
+    assert_eq!(broker.get_next_target_seq_num(), 20);
+}
+
+#[test]
+fn test_session_queue_limit() {
+    let fix = setup_fix();
+    let (mut client, mut broker) = logged_on_pair(&fix);
+    broker.set_queue_limit(2);
+    let orders: Vec<Ido> = (0..5).map(|_| sent(&fix, &client.send("D", Ido::new(), start()).unwrap()).remove(0)).collect();
+
+    // the first order is lost, only two of the later ones are held
+    assert_eq!(msgtypes(&broker.on_message(orders[1].clone(), start())), vec!["send 2"]);
+    for order in &orders[2..] {
+        assert!(broker.on_message(order.clone(), start()).is_empty());
+    }
+    let delivered = broker.on_message(orders[0].clone(), start());
+    assert_eq!(msgtypes(&delivered), vec!["deliver D", "deliver D", "deliver D"]);
+    assert_eq!(broker.get_next_target_seq_num(), 5);
+
+    // the dropped ones come again with the resend
+    assert_eq!(msgtypes(&broker.on_message(orders[3].clone(), start())), vec!["deliver D"]);
+    assert_eq!(msgtypes(&broker.on_message(orders[4].clone(), start())), vec!["deliver D"]);
+    assert!(!broker.is_resend_pending());
+}
+
+#[test]
+fn test_file_store() {
+    let directory = temp_dir("file_store");
+    let session_id = SessionID::new("FIX.4.4", "CLIENT", "BROKER");
+    let mut order = Ido::new();
+    order.set_string(&11, "ORDER1".to_string());
+    order.set_integer(&34, 2);
+    order.set_f64(&44, 101.25);
+    order.set_datetime(&52, start() + Duration::milliseconds(123));
+    order.set_bytes(&96, b"raw\x01data".to_vec());
+    let mut party = Ido::new();
+    party.set_string(&448, "TRADER".to_string());
+    party.set_integer(&452, 11);
+    order.append_array(&453, party);
+
+    let mut store = FileStore::new(&directory, &session_id).unwrap();
+    assert_eq!((store.get_next_sender_seq_num(), store.get_next_target_seq_num()), (1, 1));
+    store.save(2, "D", &order).unwrap();
+    store.set_next_sender_seq_num(3).unwrap();
+    store.set_next_target_seq_num(5).unwrap();
+    drop(store);
+
+    // reopened, the store holds what was written before
+    let mut store = FileStore::new(&directory, &session_id).unwrap();
+    assert_eq!((store.get_next_sender_seq_num(), store.get_next_target_seq_num()), (3, 5));
+    let (msgtype, message) = store.get(2).unwrap().unwrap();
+    assert_eq!(msgtype, "D");
+    assert_eq!(message.get_string(&11), Some("ORDER1".to_string()));
+    assert_eq!(message.get_i64(&34), Some(2));
+    assert_eq!(message.get_f64(&44), Some(101.25));
+    assert_eq!(message.get_datetime(&52), Some(start() + Duration::milliseconds(123)));
+    assert_eq!(message.get_bytes(&96), Some(&b"raw\x01data"[..]));
+    let parties = message.get_array(&453).unwrap();
+    assert_eq!(parties.len(), 1);
+    assert_eq!(parties[0].get_string(&448), Some("TRADER".to_string()));
+    assert_eq!(parties[0].get_i64(&452), Some(11));
+    assert!(store.get(1).unwrap().is_none());
+
+    // a record and a line torn by a crash are discarded
+    store.save(3, "D", &order).unwrap();
+    drop(store);
+    let body = format!("{}/FIX.4.4-CLIENT-BROKER.body", directory);
+    let seqnums = format!("{}/FIX.4.4-CLIENT-BROKER.seqnums", directory);
+    let len = std::fs::metadata(&body).unwrap().len();
+    std::fs::OpenOptions::new().write(true).open(&body).unwrap().set_len(len - 3).unwrap();
+    std::fs::OpenOptions::new().append(true).open(&seqnums).unwrap().write_all(b"9").unwrap();
+    let mut store = FileStore::new(&directory, &session_id).unwrap();
+    assert_eq!((store.get_next_sender_seq_num(), store.get_next_target_seq_num()), (3, 5));
+    assert!(store.get(2).unwrap().is_some());
+    assert!(store.get(3).unwrap().is_none());
+    store.save(3, "D", &order).unwrap();
+    store.set_next_sender_seq_num(4).unwrap();
+    assert!(store.get(3).unwrap().is_some());
+
+    // the sequence numbers are rewritten in place, the file does not grow
+    for seq_num in 5..100 {
+        store.set_next_sender_seq_num(seq_num).unwrap();
+    }
+    assert_eq!(std::fs::read_to_string(&seqnums).unwrap().lines().count(), 1);
+
+    store.reset().unwrap();
+    assert_eq!((store.get_next_sender_seq_num(), store.get_next_target_seq_num()), (1, 1));
+    assert!(store.get(2).unwrap().is_none());
+    drop(store);
+    let store = FileStore::new(&directory, &session_id).unwrap();
+    assert_eq!((store.get_next_sender_seq_num(), store.get_next_target_seq_num()), (1, 1));
+    assert!(store.get(2).unwrap().is_none());
+    drop(store);
+
+    // a file of appended lines is compacted to the last one on opening
+    std::fs::write(&seqnums, "3 5\n4 6\n7").unwrap();
+    let store = FileStore::new(&directory, &session_id).unwrap();
+    assert_eq!((store.get_next_sender_seq_num(), store.get_next_target_seq_num()), (4, 6));
+    assert_eq!(std::fs::read_to_string(&seqnums).unwrap().lines().count(), 1);
+    drop(store);
+    let store = FileStore::new(&directory, &session_id).unwrap();
+    assert_eq!((store.get_next_sender_seq_num(), store.get_next_target_seq_num()), (4, 6));
+}
+
+#[test]
+fn test_session_resumes_from_file_store() {
+    let fix = setup_fix();
+    let directory = temp_dir("session_resume");
+    let session_id = SessionID::new("FIX.4.4", "CLIENT", "BROKER");
+    let client_store = || Box::new(FileStore::new(&directory, &session_id).unwrap());
+
+    let mut client = Session::with_store(session_id.clone(), SessionRole::INITIATOR, 30, client_store());
+    let mut broker = acceptor();
+    broker.on_connect(start());
+    let logon = client.on_connect(start());
+    deliver(&fix, &deliver(&fix, &logon, &mut broker, start()), &mut client, start());
+    let mut order = Ido::new();
+    order.set_string(&11, "ORDER1".to_string());
+    client.send("D", order, start()).unwrap();
+    drop(client);
+    broker.on_disconnect(start());
+
+    // after a restart the client carries on from the stored sequence numbers and resends the
+    // order the broker never received
+    let mut client = Session::with_store(session_id.clone(), SessionRole::INITIATOR, 30, client_store());
+    assert_eq!(client.get_next_sender_seq_num(), 3);
+    assert_eq!(client.get_next_target_seq_num(), 2);
+    broker.on_connect(start());
+    let logon = client.on_connect(start());
+    assert_eq!(sent(&fix, &logon)[0].get_i64(&34), Some(3));
+    let response = deliver(&fix, &logon, &mut broker, start());
+    assert_eq!(msgtypes(&response), vec!["send A", "logged on", "send 2"]);
+    let replay = deliver(&fix, &response, &mut client, start());
+    assert_eq!(msgtypes(&replay), vec!["logged on", "send D", "send 4"]);
+    let delivered = deliver(&fix, &replay, &mut broker, start());
+    match &delivered[..] {
+        [SessionAction::Deliver { message, .. }] => assert_eq!(message.get_string(&11), Some("ORDER1".to_string())),
+        _ => panic!("expected the order to be delivered: {:?}", msgtypes(&delivered)),
+    }
+    assert_eq!(broker.get_next_target_seq_num(), 4);
+}
+
+#[test]
+fn test_session_reset_on_logon() {
+    let fix = setup_fix();
+    let (mut client, mut broker) = logged_on_pair(&fix);
+    client.send("D", Ido::new(), start()).unwrap();
+    client.on_disconnect(start());
+    broker.on_disconnect(start());
+    assert_eq!(client.get_next_sender_seq_num(), 3);
+
+    // the initiator asks for the reset, the acceptor follows
+    client.set_reset_on_logon(true);
+    broker.on_connect(start());
+    let logon = client.on_connect(start());
+    let message = &sent(&fix, &logon)[0];
+    assert_eq!(message.get_i64(&34), Some(1));
+    assert_eq!(message.get_string(&141), Some("Y".to_string()));
+    let response = deliver(&fix, &logon, &mut broker, start());
+    assert_eq!(msgtypes(&response), vec!["send A", "logged on"]);
+    let message = &sent(&fix, &response)[0];
+    assert_eq!(message.get_i64(&34), Some(1));
+    assert_eq!(message.get_string(&141), Some("Y".to_string()));
+    assert_eq!(msgtypes(&deliver(&fix, &response, &mut client, start())), vec!["logged on"]);
+    for session in [&client, &broker] {
+        assert_eq!((session.get_next_sender_seq_num(), session.get_next_target_seq_num()), (2, 2));
+    }
+
+    // configured from properties, the store is a file store in the given directory
+    let _ = std::fs::remove_dir_all("target/tmp/session_store");
+    let properties = PropertiesBuilder::new().with_file("tests/config/session.properties").build().unwrap();
+    let mut broker = acceptor();
+    assert!(!broker.get_reset_on_logon());
+    broker.init(&properties).unwrap();
+    assert!(broker.get_reset_on_logon());
+    assert!(std::path::Path::new("target/tmp/session_store/FIX.4.4-BROKER-CLIENT.seqnums").exists());
+}
+
+#[test]
+fn test_session_settings() {
+    let properties = PropertiesBuilder::new().with_file("tests/config/sessions.properties").build().unwrap();
+    let settings = SessionSettings::load_all(&properties).unwrap();
+    assert_eq!(settings.iter().map(|s| s.get_name()).collect::<Vec<_>>(), vec!["client", "broker", "weekly"]);
+
+    let client = &settings[0];
+    assert_eq!(client.get_session_id(), &SessionID::new("FIX.4.4", "CLIENT", "BROKER"));
+    assert_eq!(client.get_role(), SessionRole::INITIATOR);
+    assert_eq!(client.get_address(), "localhost:9876");
+    assert_eq!(client.get_heartbeat_interval(), 30);
+    assert_eq!(client.get_reconnect_interval(), std::time::Duration::from_secs(5));
+    let time = |h, m, s| NaiveTime::from_hms_opt(h, m, s).unwrap();
+    assert_eq!(client.get_schedule(), Some(SessionSchedule::daily(time(7, 0, 0), time(21, 0, 0))));
+    assert_eq!(client.get_dictionaries(), ["tests/config/FIX44.xml"]);
+    assert_eq!(client.get_strictness(), Strictness::WARN);
+    assert!(client.get_reset_on_logon());
+    assert_eq!(client.get_store_path(), None);
+
+    // overrides of the shared values, the port through the namespace.class.app hierarchy
+    let broker = &settings[1];
+    assert_eq!(broker.get_role(), SessionRole::ACCEPTOR);
+    assert_eq!(broker.get_address(), "0.0.0.0:9877");
+    assert_eq!(broker.get_heartbeat_interval(), 60);
+    assert_eq!(broker.get_reconnect_interval(), std::time::Duration::from_secs(30));
+    assert_eq!(broker.get_schedule(), None);
+    assert!(!broker.get_reset_on_logon());
+    assert_eq!(broker.get_store_path(), Some("target/tmp/settings_store"));
+
+    let weekly = &settings[2];
+    assert_eq!(weekly.get_session_id(), &SessionID::new("FIXT.1.1", "CLIENT", "VENUE"));
+    assert_eq!(weekly.get_dictionaries(), ["tests/config/FIXT11.xml", "tests/config/FIX50SP2.xml"]);
+    assert_eq!(weekly.get_strictness(), Strictness::STRICT);
+    assert_eq!(weekly.get_schedule(), Some(SessionSchedule::weekly(Weekday::Sun, time(22, 0, 0), Weekday::Fri, time(22, 0, 0))));
+
+    // the codec and session are created from the settings
+    let fix = weekly.create_fix().unwrap();
+    assert!(fix.get_dictionary_for("FIXT.1.1", Some("9")).is_some());
+    assert_eq!(client.create_fix().unwrap().get_strictness(), Strictness::WARN);
+    let session = client.create_session().unwrap();
+    assert_eq!(session.get_session_id(), client.get_session_id());
+    assert!(session.get_reset_on_logon());
+    assert_eq!(session.get_schedule(), client.get_schedule());
+    let _ = std::fs::remove_dir_all("target/tmp/settings_store");
+    let session = broker.create_session().unwrap();
+    assert_eq!((session.get_role(), session.get_heartbeat_interval()), (SessionRole::ACCEPTOR, 60));
+    assert!(std::path::Path::new("target/tmp/settings_store/FIX.4.4-BROKER-CLIENT.seqnums").exists());
+
+    assert!(matches!(SessionSettings::load(&properties, "invalid"), Err(extrans::ExtransError::ParseError(_))));
+    assert!(matches!(SessionSettings::load(&properties, "unknown"), Err(extrans::ExtransError::PropertyNotFound(_))));
+}
+
+#[test]
+fn test_session_schedule() {
+    let time = |h, m, s| NaiveTime::from_hms_opt(h, m, s).unwrap();
+    let at = |d, h, m| Utc.with_ymd_and_hms(2024, 1, d, h, m, 0).unwrap();
+
+    // 2024-01-01 is a Monday
+    let daily = SessionSchedule::daily(time(7, 0, 0), time(21, 0, 0));
+    assert!(!daily.is_in_session(at(1, 6, 59)));
+    assert!(daily.is_in_session(at(1, 7, 0)));
+    assert!(!daily.is_in_session(at(1, 21, 0)));
+    let overnight = SessionSchedule::daily(time(21, 0, 0), time(7, 0, 0));
+    assert!(overnight.is_in_session(at(1, 23, 0)));
+    assert!(overnight.is_in_session(at(2, 6, 0)));
+    assert!(!overnight.is_in_session(at(2, 12, 0)));
+    let weekly = SessionSchedule::weekly(Weekday::Sun, time(22, 0, 0), Weekday::Fri, time(22, 0, 0));
+    assert!(weekly.is_in_session(at(3, 3, 0)));
+    assert!(!weekly.is_in_session(at(5, 22, 0)));
+    assert!(!weekly.is_in_session(at(6, 12, 0)));
+    assert!(weekly.is_in_session(at(7, 22, 0)));
+
+    // the session logs out when the schedule ends and refuses to log on outside it
+    let fix = setup_fix();
+    let (mut client, _) = logged_on_pair(&fix);
+    client.set_schedule(Some(SessionSchedule::daily(time(7, 0, 0), time(12, 0, 1))));
+    assert!(client.on_timer(start()).is_empty());
+    assert_eq!(msgtypes(&client.on_timer(start() + Duration::seconds(1))), vec!["send 5"]);
+    assert_eq!(client.get_state(), SessionState::LOGOUT_SENT);
+
+    let (mut client, mut broker) = (initiator(), acceptor());
+    broker.set_schedule(Some(SessionSchedule::daily(time(13, 0, 0), time(21, 0, 0))));
+    broker.on_connect(start());
+    let logon = client.on_connect(start());
+    assert_eq!(msgtypes(&deliver(&fix, &logon, &mut broker, start())), vec!["disconnect"]);
+    assert_eq!(broker.get_state(), SessionState::DISCONNECTED);
+}